@@ -2,12 +2,16 @@ use criterion::{criterion_group, criterion_main, Bencher, Criterion, Throughput}
 
 mod count;
 mod input;
+mod set;
+mod size;
 mod u16;
 mod u32;
 
 fn all(c: &mut Criterion) {
     u16::all(c);
     u32::all(c);
+    size::all(c);
+    set::all(c);
 }
 
 fn define(
@@ -1,5 +1,7 @@
 use wmemchr::fallback;
 use wmemchr::naive;
+#[cfg(target_arch = "x86")]
+use wmemchr::x86 as x86_64;
 #[cfg(target_arch = "x86_64")]
 use wmemchr::x86_64;
 use wmemchr::Wide;
@@ -24,7 +26,7 @@ pub fn naive<T: Wide>(needle: T, haystack: &[T]) -> usize {
     count
 }
 
-#[cfg(target_arch = "x86_64")]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub fn x86_64<T: Wide>(needle: T, haystack: &[T]) -> usize {
     let mut count = 0;
     let mut start = 0;
@@ -34,3 +36,21 @@ pub fn x86_64<T: Wide>(needle: T, haystack: &[T]) -> usize {
     }
     count
 }
+
+/// Counts with libc `wmemchr`, whose `wchar_t` is 32-bit on Linux.
+#[cfg(target_os = "linux")]
+pub fn libc(needle: u32, haystack: &[u32]) -> usize {
+    let mut count = 0;
+    let mut ptr = haystack.as_ptr() as *const libc::wchar_t;
+    let end = haystack.as_ptr_range().end as *const libc::wchar_t;
+    loop {
+        let len = unsafe { end.offset_from(ptr) } as usize;
+        let found = unsafe { libc::wmemchr(ptr, needle as libc::wchar_t, len) };
+        if found.is_null() {
+            break;
+        }
+        count += 1;
+        ptr = unsafe { found.add(1) };
+    }
+    count
+}
@@ -0,0 +1,85 @@
+use criterion::Criterion;
+use wmemchr::naive;
+
+use crate::input::HUGE_U16;
+
+pub fn all(c: &mut Criterion) {
+    // Search for characters that never occur, so the whole corpus is searched.
+    let corpus = HUGE_U16.corpus;
+    let never: Vec<u16> = HUGE_U16.never.iter().map(|c| c.value).collect();
+    // The range from '<' to '>' also contains '=', which never occurs either.
+    let (start, end) = (never[0], never[1]);
+
+    macro_rules! def {
+        ($group:literal, $any:path, $range:path) => {
+            // Skip the group if its backend cannot be forced, such as when
+            // another backend is statically dispatched to.
+            if $crate::set::can_force($group) {
+                for len in 1..=never.len() {
+                    let needles = never[..len].to_vec();
+                    let name = format!("u16/set/{}/any/{}", $group, len);
+                    $crate::define(
+                        c,
+                        &name,
+                        corpus.len(),
+                        Box::new(move |b| {
+                            $crate::set::force($group);
+                            b.iter(|| assert_eq!(None, $any(&needles, corpus)));
+                        }),
+                    );
+                }
+
+                let name = format!("u16/set/{}/range", $group);
+                $crate::define(
+                    c,
+                    &name,
+                    corpus.len(),
+                    Box::new(move |b| {
+                        $crate::set::force($group);
+                        b.iter(|| assert_eq!(None, $range(start..=end, corpus)));
+                    }),
+                );
+            }
+        };
+    }
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        // The SSE2 backend uses the SSE4.2 string instructions, if supported.
+        if is_x86_feature_detected!("sse4.2") {
+            def!("sse42", wmemchr::wmemchr_any, wmemchr::wmemchr_range);
+        }
+        def!("avx2", wmemchr::wmemchr_any, wmemchr::wmemchr_range);
+    }
+    def!("naive", naive::wmemchr_any, naive::wmemchr_range);
+}
+
+/// Returns the backend that the dispatched set and range searches are forced
+/// to use for a group, if any.
+///
+/// Forcing a backend takes precedence over the `WMEMCHR_BACKEND` environment
+/// variable, which does not apply to these groups.
+fn backend(group: &str) -> Option<wmemchr::Backend> {
+    use wmemchr::Backend;
+
+    match group {
+        "sse42" => Some(Backend::Sse2),
+        "avx2" => Some(Backend::Avx2),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the backend of a group can be forced.
+fn can_force(group: &str) -> bool {
+    match backend(group) {
+        Some(backend) => backend.is_supported(),
+        None => true,
+    }
+}
+
+/// Forces the backend used by the dispatched set and range searches.
+fn force(group: &str) {
+    if let Some(backend) = backend(group) {
+        // Only groups whose backend can be forced are defined.
+        wmemchr::force_backend(backend).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+use criterion::Criterion;
+
+use crate::count;
+use crate::input::{HUGE_U16, HUGE_U32};
+
+/// Haystack lengths around the thresholds between size classes.
+const LENGTHS: &[usize] = &[
+    1, 4, 7, 8, 15, 16, 31, 32, 64, 256, 1024, 2048, 4096, 8192, 16384, 65536, 262144,
+];
+
+pub fn all(c: &mut Criterion) {
+    macro_rules! def {
+        ($group:literal, $fn:path) => {
+            for &len in LENGTHS {
+                // Search a prefix of the corpus for a character that never
+                // occurs, so the whole haystack is searched.
+                let search = HUGE_U16.never().unwrap();
+                let corpus = &search.corpus[..len];
+                let name = format!("u16/{}/size/{}", $group, len);
+                $crate::define(
+                    c,
+                    &name,
+                    len,
+                    Box::new(move |b| {
+                        b.iter(|| assert_eq!(0, $fn(search.value.value, corpus)));
+                    }),
+                );
+
+                let search = HUGE_U32.never().unwrap();
+                let corpus = &search.corpus[..len];
+                let name = format!("u32/{}/size/{}", $group, len);
+                $crate::define(
+                    c,
+                    &name,
+                    len,
+                    Box::new(move |b| {
+                        b.iter(|| assert_eq!(0, $fn(search.value.value, corpus)));
+                    }),
+                );
+            }
+        };
+    }
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        def!("x86_64", count::x86_64);
+    }
+    def!("fallback", count::fallback);
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    huge(c);
+}
+
+/// The corpus of huge haystacks as bytes, in which `<` never occurs.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+const HUGE_U8: &[u8] = include_bytes!("../data/sherlock/huge.txt");
+
+/// Haystack lengths, in bytes, around the sizes from which the AVX2 and EVEX
+/// kernels use the loop unrolled over 8 vectors.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+const HUGE_SIZES: &[usize] = &[
+    256, 384, 512, 768, 1024, 2048, 4096, 8192, 16384, 32768, 65536, 262144,
+];
+
+/// Compares the AVX2 and EVEX loops with and without the loop unrolled over 8
+/// vectors, to measure the size from which it is used.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn huge(c: &mut Criterion) {
+    #[cfg(target_arch = "x86")]
+    use wmemchr::x86::huge;
+    #[cfg(target_arch = "x86_64")]
+    use wmemchr::x86_64::huge;
+
+    macro_rules! def {
+        ($group:literal, $u8:path, $u16:path, $u32:path) => {
+            for &size in HUGE_SIZES {
+                for &(loop_name, wide) in &[("4x", false), ("8x", true)] {
+                    let corpus = &HUGE_U8[..size];
+                    let name = format!("u8/{}/huge/{}/{}", $group, loop_name, size);
+                    $crate::define(
+                        c,
+                        &name,
+                        corpus.len(),
+                        Box::new(move |b| {
+                            b.iter(|| unsafe { assert_eq!(None, $u8(b'<', corpus, wide)) });
+                        }),
+                    );
+
+                    let search = HUGE_U16.never().unwrap();
+                    let corpus = &search.corpus[..size / 2];
+                    let name = format!("u16/{}/huge/{}/{}", $group, loop_name, size);
+                    $crate::define(
+                        c,
+                        &name,
+                        corpus.len(),
+                        Box::new(move |b| {
+                            b.iter(|| unsafe {
+                                assert_eq!(None, $u16(search.value.value, corpus, wide))
+                            });
+                        }),
+                    );
+
+                    let search = HUGE_U32.never().unwrap();
+                    let corpus = &search.corpus[..size / 4];
+                    let name = format!("u32/{}/huge/{}/{}", $group, loop_name, size);
+                    $crate::define(
+                        c,
+                        &name,
+                        corpus.len(),
+                        Box::new(move |b| {
+                            b.iter(|| unsafe {
+                                assert_eq!(None, $u32(search.value.value, corpus, wide))
+                            });
+                        }),
+                    );
+                }
+            }
+        };
+    }
+    if is_x86_feature_detected!("avx2") {
+        def!("avx2", huge::avx2_u8, huge::avx2_u16, huge::avx2_u32);
+    }
+    #[cfg(feature = "unstable")]
+    {
+        if is_x86_feature_detected!("avx512vl") && is_x86_feature_detected!("avx512bw") {
+            def!("evex", huge::evex_u8, huge::evex_u16, huge::evex_u32);
+        }
+    }
+}
@@ -25,7 +25,7 @@ pub fn all(c: &mut Criterion) {
             });
         };
     }
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
         def!("x86_64", count::x86_64);
     }
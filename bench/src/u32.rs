@@ -25,12 +25,54 @@ pub fn all(c: &mut Criterion) {
             });
         };
     }
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
         def!("x86_64", count::x86_64);
     }
     def!("fallback", count::fallback);
     def!("naive", count::naive);
+    #[cfg(target_os = "linux")]
+    {
+        def!("libc", count::libc);
+    }
+
+    wcslen(c);
+}
+
+/// Finds the null terminator of each corpus, as `wcslen` does.
+fn wcslen(c: &mut Criterion) {
+    macro_rules! def {
+        ($name:literal, $fn:expr) => {
+            for &(size, input) in &[
+                ("huge", HUGE),
+                ("small", SMALL),
+                ("tiny", TINY),
+                ("empty", EMPTY),
+            ] {
+                let mut s = input.corpus.to_vec();
+                s.push(0);
+
+                let len = input.corpus.len();
+                let name = format!("u32/wcslen/{}/{}", size, $name);
+                $crate::define(
+                    c,
+                    &name,
+                    len,
+                    Box::new(move |b| b.iter(|| assert_eq!(len, $fn(&s)))),
+                );
+            }
+        };
+    }
+    def!("wmemchr", |s: &[u32]| wmemchr::wmemchr(0, s).unwrap());
+    def!("wchar", |s: &[u32]| unsafe {
+        wmemchr::wchar::wcslen(s.as_ptr() as *const wmemchr::wchar::wchar_t)
+    });
+    #[cfg(target_os = "linux")]
+    {
+        def!("libc", |s: &[u32]| unsafe {
+            libc::wcslen(s.as_ptr() as *const libc::wchar_t)
+        });
+    }
 }
 
 fn define_wmemchr_input(
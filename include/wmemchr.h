@@ -0,0 +1,56 @@
+/*
+ * C ABI exports of the wmemchr crate, built with the `capi` feature.
+ *
+ * The functions follow the signatures of their libc counterparts, with 16-bit
+ * and 32-bit variants in place of `wchar_t`. Haystacks must be aligned to the
+ * size of their wide characters.
+ */
+
+#ifndef WMEMCHR_H
+#define WMEMCHR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Returns a pointer to the first occurrence of `c` in the `n` wide characters
+ * starting at `s`, or a null pointer if it is not found.
+ */
+uint16_t *wmemchr16(const uint16_t *s, uint16_t c, size_t n);
+uint32_t *wmemchr32(const uint32_t *s, uint32_t c, size_t n);
+
+/*
+ * Returns a pointer to the last occurrence of `c` in the `n` wide characters
+ * starting at `s`, or a null pointer if it is not found.
+ */
+uint16_t *wmemrchr16(const uint16_t *s, uint16_t c, size_t n);
+uint32_t *wmemrchr32(const uint32_t *s, uint32_t c, size_t n);
+
+/*
+ * Returns the number of wide characters before the null terminator of the
+ * string `s`.
+ */
+size_t wcslen16(const uint16_t *s);
+size_t wcslen32(const uint32_t *s);
+
+/*
+ * Returns the number of wide characters before the null terminator of the
+ * string `s`, or `maxlen` if none of the first `maxlen` wide characters is a
+ * null terminator.
+ *
+ * As with libc `wcsnlen`, the search stops at the null terminator, so only
+ * the wide characters up to and including it, or the first `maxlen` if there
+ * is none, must be readable.
+ */
+size_t wcsnlen16(const uint16_t *s, size_t maxlen);
+size_t wcsnlen32(const uint32_t *s, size_t maxlen);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* WMEMCHR_H */
@@ -0,0 +1,252 @@
+//! Selection and introspection of the kernels used by the search functions.
+
+use core::fmt;
+
+use crate::char::{Wide, WideRepr};
+#[cfg(all(
+    feature = "unstable",
+    any(miri, not(any(target_arch = "x86", target_arch = "x86_64")))
+))]
+use crate::portable;
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+use crate::x86_64;
+
+/// A family of kernels that the search functions can be dispatched to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum Backend {
+    /// The platform independent implementation, see [`fallback`].
+    ///
+    /// [`fallback`]: crate::fallback
+    Fallback = 0,
+    /// The portable SIMD implementation, see the `portable` module.
+    ///
+    /// This is only available with the `unstable` feature, on targets other
+    /// than x86_64 and under Miri.
+    Portable = 1,
+    /// The x86_64 kernels using SSE2 instructions.
+    Sse2 = 2,
+    /// The x86_64 kernels using AVX2 instructions.
+    Avx2 = 3,
+    /// The x86_64 kernels using EVEX encoded AVX-512 instructions.
+    ///
+    /// These kernels are only available with the `unstable` feature.
+    Evex = 4,
+}
+
+impl Backend {
+    /// All backends, in order from least to most preferred.
+    pub const ALL: [Backend; 5] = [
+        Backend::Fallback,
+        Backend::Portable,
+        Backend::Sse2,
+        Backend::Avx2,
+        Backend::Evex,
+    ];
+
+    /// Returns the name of the backend.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use wmemchr::Backend;
+    ///
+    /// assert_eq!(Backend::Avx2.name(), "avx2");
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Fallback => "fallback",
+            Backend::Portable => "portable",
+            Backend::Sse2 => "sse2",
+            Backend::Avx2 => "avx2",
+            Backend::Evex => "evex",
+        }
+    }
+
+    /// Returns `true` if the backend can be used on the current CPU, with the
+    /// features this crate was compiled with.
+    ///
+    /// When the target features enabled at compile time guarantee support for
+    /// the AVX2 (or, with the `unstable` feature, the EVEX) kernels, these are
+    /// called directly rather than through runtime dispatch, and no other
+    /// backend is supported.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use wmemchr::{backend, Backend};
+    ///
+    /// assert!(backend::<u16>().is_supported());
+    ///
+    /// for &b in Backend::ALL.iter() {
+    ///     println!("{}: {}", b, b.is_supported());
+    /// }
+    /// ```
+    pub fn is_supported(self) -> bool {
+        cfg_if::cfg_if! {
+            if #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))] {
+                x86_64::is_supported(self)
+            } else if #[cfg(feature = "unstable")] {
+                self == Backend::Fallback || self == Backend::Portable
+            } else {
+                self == Backend::Fallback
+            }
+        }
+    }
+
+    /// Returns the backend requested by the `WMEMCHR_BACKEND` environment
+    /// variable, if it names a supported backend.
+    ///
+    /// This is only read on targets with more than one backend.
+    #[cfg(all(
+        feature = "std",
+        any(
+            feature = "unstable",
+            all(not(miri), any(target_arch = "x86", target_arch = "x86_64")),
+        ),
+    ))]
+    pub(crate) fn from_env() -> Option<Backend> {
+        let name = std::env::var_os("WMEMCHR_BACKEND")?;
+
+        Backend::ALL
+            .iter()
+            .copied()
+            .find(|backend| name == backend.name())
+            .filter(|backend| backend.is_supported())
+    }
+
+    #[cfg(any(
+        feature = "unstable",
+        all(not(miri), any(target_arch = "x86", target_arch = "x86_64")),
+    ))]
+    pub(crate) fn from_u8(value: u8) -> Option<Backend> {
+        Backend::ALL.get(value as usize).copied()
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An error returned by [`force_backend`] when the backend is not supported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnsupportedBackend {
+    backend: Backend,
+}
+
+impl UnsupportedBackend {
+    /// Returns the backend that is not supported.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+}
+
+impl fmt::Display for UnsupportedBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the {} backend is not supported", self.backend)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnsupportedBackend {}
+
+/// Returns the backend used to search slices of `T`.
+///
+/// Each element width has its own kernels, which are selected from the backend
+/// when it is detected or [forced](force_backend). Within a backend, some
+/// searches use a more specific kernel: on x86_64, the SSE2 backend searches
+/// for sets and ranges of 16-bit wide characters with the SSE4.2 string
+/// instructions, if supported. A [registered] custom kernel takes precedence
+/// over the backend for the operations it implements.
+///
+/// This runs CPU feature detection, if it has not already been run.
+///
+/// With the `std` feature, the detected backend can be overridden by setting
+/// the `WMEMCHR_BACKEND` environment variable to the [name] of a backend, on
+/// every target. This is ignored if the name is unknown or the backend is not
+/// supported.
+///
+/// [registered]: crate::register_kernel
+/// [name]: Backend::name
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::backend;
+///
+/// println!("searching u8 slices with {}", backend::<u8>());
+/// println!("searching u16 slices with {}", backend::<u16>());
+/// ```
+#[inline]
+pub fn backend<T: WideRepr>() -> Backend {
+    T::Repr::backend()
+}
+
+/// Overrides the backend used by the search functions, for all element types.
+///
+/// This is intended for testing and benchmarking particular kernels. Returns
+/// an error, without changing the backend, if the backend is not supported.
+/// A forced backend takes precedence over the `WMEMCHR_BACKEND` environment
+/// variable.
+///
+/// When the target features enabled at compile time guarantee support for the
+/// AVX2 (or, with the `unstable` feature, the EVEX) kernels, these are called
+/// directly, and forcing any other backend returns an error. Callers iterating
+/// over backends should skip those that are not [supported].
+///
+/// [supported]: Backend::is_supported
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::{backend, force_backend, Backend};
+///
+/// if force_backend(Backend::Fallback).is_ok() {
+///     assert_eq!(backend::<u8>(), Backend::Fallback);
+///     assert_eq!(backend::<u16>(), Backend::Fallback);
+/// }
+/// ```
+pub fn force_backend(backend: Backend) -> Result<(), UnsupportedBackend> {
+    if !backend.is_supported() {
+        return Err(UnsupportedBackend { backend });
+    }
+
+    cfg_if::cfg_if! {
+        if #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))] {
+            x86_64::set_backend(backend);
+        } else if #[cfg(feature = "unstable")] {
+            portable::set_backend(backend);
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs CPU feature detection and selects the kernels for every search
+/// function up front.
+///
+/// Otherwise this is done lazily, on the first call to each search function.
+/// Calling `init` more than once has no further effect.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// wmemchr::init();
+/// ```
+#[inline]
+pub fn init() {
+    #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+    x86_64::init();
+}
@@ -0,0 +1,162 @@
+//! Search functions for wide characters stored in byte slices.
+
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+use crate::x86_64;
+
+/// Returns the index of the first occurrence of a 16-bit wide character in a
+/// byte slice of little-endian code units, or [`None`] if the character is not
+/// found.
+///
+/// The haystack does not need to be aligned to a 16-bit boundary, and the
+/// returned index counts code units rather than bytes. A trailing byte that
+/// does not form a whole code unit is ignored.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemchr_bytes_le;
+///
+/// let bytes = b"_f\0o\0o\0 \0b\0a\0r\0";
+/// let haystack = &bytes[1..];
+///
+/// assert_eq!(wmemchr_bytes_le(u16::from(b'o'), haystack), Some(1));
+/// assert_eq!(wmemchr_bytes_le(u16::from(b'z'), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr_bytes_le(needle: u16, haystack: &[u8]) -> Option<usize> {
+    wmemchr_bytes16(needle.to_le(), haystack)
+}
+
+/// Returns the index of the first occurrence of a 16-bit wide character in a
+/// byte slice of big-endian code units, or [`None`] if the character is not
+/// found.
+///
+/// The haystack does not need to be aligned to a 16-bit boundary, and the
+/// returned index counts code units rather than bytes. A trailing byte that
+/// does not form a whole code unit is ignored.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemchr_bytes_be;
+///
+/// let bytes = b"_\0f\0o\0o\0 \0b\0a\0r";
+/// let haystack = &bytes[1..];
+///
+/// assert_eq!(wmemchr_bytes_be(u16::from(b'o'), haystack), Some(1));
+/// assert_eq!(wmemchr_bytes_be(u16::from(b'z'), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr_bytes_be(needle: u16, haystack: &[u8]) -> Option<usize> {
+    wmemchr_bytes16(needle.to_be(), haystack)
+}
+
+/// Returns the index of the first occurrence of a 32-bit wide character in a
+/// byte slice of little-endian code units, or [`None`] if the character is not
+/// found.
+///
+/// The haystack does not need to be aligned to a 32-bit boundary, and the
+/// returned index counts code units rather than bytes. Trailing bytes that do
+/// not form a whole code unit are ignored.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemchr32_bytes_le;
+///
+/// let bytes = b"_f\0\0\0o\0\0\0o\0\0\0";
+/// let haystack = &bytes[1..];
+///
+/// assert_eq!(wmemchr32_bytes_le(u32::from(b'o'), haystack), Some(1));
+/// assert_eq!(wmemchr32_bytes_le(u32::from(b'z'), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr32_bytes_le(needle: u32, haystack: &[u8]) -> Option<usize> {
+    wmemchr_bytes32(needle.to_le(), haystack)
+}
+
+/// Returns the index of the first occurrence of a 32-bit wide character in a
+/// byte slice of big-endian code units, or [`None`] if the character is not
+/// found.
+///
+/// The haystack does not need to be aligned to a 32-bit boundary, and the
+/// returned index counts code units rather than bytes. Trailing bytes that do
+/// not form a whole code unit are ignored.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemchr32_bytes_be;
+///
+/// let bytes = b"_\0\0\0f\0\0\0o\0\0\0o";
+/// let haystack = &bytes[1..];
+///
+/// assert_eq!(wmemchr32_bytes_be(u32::from(b'o'), haystack), Some(1));
+/// assert_eq!(wmemchr32_bytes_be(u32::from(b'z'), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr32_bytes_be(needle: u32, haystack: &[u8]) -> Option<usize> {
+    wmemchr_bytes32(needle.to_be(), haystack)
+}
+
+macro_rules! wmemchr_bytes {
+    ($($name:ident: $ty:ident;)*) => {
+        $(
+            /// Searches a byte slice of native endian code units.
+            #[inline(always)]
+            fn $name(needle: $ty, haystack: &[u8]) -> Option<usize> {
+                let pos = {
+                    cfg_if::cfg_if! {
+                        if #[cfg(miri)] {
+                            wmemchr_bytes!(@fallback $ty, needle, haystack)
+                        } else if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
+                            x86_64::$name(needle, haystack)
+                        } else {
+                            wmemchr_bytes!(@fallback $ty, needle, haystack)
+                        }
+                    }
+                };
+
+                #[cfg(feature = "checked")]
+                crate::checked::check::<$ty, _, _, _>(
+                    stringify!($name),
+                    false,
+                    needle,
+                    haystack,
+                    pos,
+                    wmemchr_bytes!(@naive $ty, needle, haystack),
+                );
+
+                pos
+            }
+        )*
+    };
+    (@naive $ty:ident, $needle:expr, $haystack:expr) => {
+        crate::const_fn::$ty::const_wmemchr_ne_bytes($needle, $haystack)
+    };
+    (@fallback $ty:ident, $needle:expr, $haystack:expr) => {{
+        // SAFETY: Any bit pattern is a valid integer.
+        let (prefix, units, _) = unsafe { $haystack.align_to::<$ty>() };
+
+        // If the haystack happens to be aligned, then it can be searched as a
+        // slice of code units.
+        if prefix.is_empty() {
+            crate::fallback::wmemchr($needle, units)
+        } else {
+            wmemchr_bytes!(@naive $ty, $needle, $haystack)
+        }
+    }};
+}
+
+wmemchr_bytes! {
+    wmemchr_bytes16: u16;
+    wmemchr_bytes32: u32;
+}
@@ -0,0 +1,94 @@
+//! C ABI exports of the search functions, for C callers.
+//!
+//! This is only available with the `capi` feature. The functions are declared
+//! in the C header `include/wmemchr.h`, and follow the signatures of their
+//! libc counterparts, with 16-bit and 32-bit variants in place of `wchar_t`.
+//!
+//! To build a shared or static library, pass the crate type to Cargo:
+//!
+//! ```text
+//! cargo rustc --release --features capi --lib --crate-type cdylib
+//! cargo rustc --release --features capi --lib --crate-type staticlib
+//! ```
+
+use crate::raw::{self, wmemchr_raw, wmemrchr_raw};
+
+macro_rules! capi {
+    ($(
+        $ty:ident {
+            wmemchr: $wmemchr:ident,
+            wmemrchr: $wmemrchr:ident,
+            wcslen: $wcslen:ident,
+            wcsnlen: $wcsnlen:ident,
+        }
+    )*) => {
+        $(
+            /// Returns a pointer to the first occurrence of `c` in the `n` wide
+            /// characters starting at `s`, or a null pointer if it is not
+            /// found.
+            ///
+            /// # Safety
+            ///
+            /// Unless `n` is zero, `s` must be non-null, aligned, and valid for
+            /// reads of `n` wide characters.
+            #[no_mangle]
+            pub unsafe extern "C" fn $wmemchr(s: *const $ty, c: $ty, n: usize) -> *mut $ty {
+                wmemchr_raw(c, s, n) as *mut $ty
+            }
+
+            /// Returns a pointer to the last occurrence of `c` in the `n` wide
+            /// characters starting at `s`, or a null pointer if it is not
+            /// found.
+            ///
+            /// # Safety
+            ///
+            /// Unless `n` is zero, `s` must be non-null, aligned, and valid for
+            /// reads of `n` wide characters.
+            #[no_mangle]
+            pub unsafe extern "C" fn $wmemrchr(s: *const $ty, c: $ty, n: usize) -> *mut $ty {
+                wmemrchr_raw(c, s, n) as *mut $ty
+            }
+
+            /// Returns the number of wide characters before the null
+            /// terminator of the string `s`.
+            ///
+            /// # Safety
+            ///
+            /// `s` must be non-null, aligned, and valid for reads up to and
+            /// including its null terminator.
+            #[no_mangle]
+            pub unsafe extern "C" fn $wcslen(s: *const $ty) -> usize {
+                raw::$wcslen(s)
+            }
+
+            /// Returns the number of wide characters before the null
+            /// terminator of the string `s`, or `maxlen` if none of the first
+            /// `maxlen` wide characters is a null terminator.
+            ///
+            /// # Safety
+            ///
+            /// Unless `maxlen` is zero, `s` must be non-null, aligned, and valid
+            /// for reads up to and including its null terminator or its
+            /// `maxlen`th wide character, whichever comes first.
+            #[no_mangle]
+            pub unsafe extern "C" fn $wcsnlen(s: *const $ty, maxlen: usize) -> usize {
+                raw::$wcsnlen(s, maxlen)
+            }
+        )*
+    };
+}
+
+capi! {
+    u16 {
+        wmemchr: wmemchr16,
+        wmemrchr: wmemrchr16,
+        wcslen: wcslen16,
+        wcsnlen: wcsnlen16,
+    }
+    u32 {
+        wmemchr: wmemchr32,
+        wmemrchr: wmemrchr32,
+        wcslen: wcslen32,
+        wcsnlen: wcsnlen32,
+    }
+}
@@ -1,51 +1,351 @@
+use core::{fmt, mem, slice};
+
+use crate::const_fn;
 use crate::fallback;
+use crate::kernel::Registry;
 use crate::naive;
 
-#[cfg(all(not(miri), target_arch = "x86_64"))]
+use crate::backend::Backend;
+#[cfg(feature = "unstable")]
+use crate::portable;
+
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
 use crate::x86_64;
 
 mod private {
     pub trait Sealed {}
 }
 
-/// A `wmemchr` kernel.
+/// A `wmemchr` kernel, searching the `len` elements starting at `haystack`.
+///
+/// # Safety
+///
+/// The kernel functions require `haystack` to be non-null, aligned, and valid
+/// for reads of `len` elements.
 pub(crate) trait KernelFn<T> {
     /// The kernel function.
-    fn kernel(needle: T, haystack: &[T]) -> Option<usize>;
+    unsafe fn kernel(needle: T, haystack: *const T, len: usize) -> Option<usize>;
+    /// The kernel function searching for the last occurrence.
+    unsafe fn kernel_reverse(needle: T, haystack: *const T, len: usize) -> Option<usize>;
+    /// The kernel function counting the occurrences.
+    unsafe fn kernel_count(needle: T, haystack: *const T, len: usize) -> usize;
+    /// The backend of the kernel functions, selecting them if needed.
+    fn backend() -> Backend;
 }
 
 /// A trait for wide character types.
-pub trait Wide: private::Sealed + Copy + Eq + 'static {
+///
+/// This is implemented for the 8-bit, 16-bit and 32-bit integer types, as well
+/// as [`char`], which is searched as a 32-bit wide character.
+pub trait Wide: private::Sealed + Copy + Ord + fmt::Debug + 'static {
+    #[doc(hidden)]
+    fn registry() -> &'static Registry<Self>;
+    #[doc(hidden)]
+    fn backend() -> Backend;
+
     #[doc(hidden)]
     fn wmemchr_naive(needle: Self, haystack: &[Self]) -> Option<usize>;
     #[doc(hidden)]
-    fn wmemchr_fallback(needle: Self, haystack: &[Self]) -> Option<usize>;
+    fn wmemrchr_naive(needle: Self, haystack: &[Self]) -> Option<usize>;
+    #[doc(hidden)]
+    unsafe fn wmemchr_fallback(needle: Self, haystack: *const Self, len: usize) -> Option<usize>;
     #[doc(hidden)]
-    #[cfg(all(not(miri), target_arch = "x86_64"))]
-    fn wmemchr_x86_64(needle: Self, haystack: &[Self]) -> Option<usize>;
+    unsafe fn wmemrchr_fallback(needle: Self, haystack: *const Self, len: usize) -> Option<usize>;
+    #[doc(hidden)]
+    unsafe fn wmemcount_fallback(needle: Self, haystack: *const Self, len: usize) -> usize;
+    #[doc(hidden)]
+    #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+    unsafe fn wmemchr_x86_64(needle: Self, haystack: *const Self, len: usize) -> Option<usize>;
+    #[doc(hidden)]
+    #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+    unsafe fn wmemrchr_x86_64(needle: Self, haystack: *const Self, len: usize) -> Option<usize>;
+    #[doc(hidden)]
+    #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+    unsafe fn wmemcount_x86_64(needle: Self, haystack: *const Self, len: usize) -> usize;
+
+    #[doc(hidden)]
+    #[cfg(feature = "unstable")]
+    fn wmemchr_portable(needle: Self, haystack: &[Self]) -> Option<usize>;
+    #[doc(hidden)]
+    #[cfg(feature = "unstable")]
+    fn wmemrchr_portable(needle: Self, haystack: &[Self]) -> Option<usize>;
+    #[doc(hidden)]
+    #[cfg(feature = "unstable")]
+    fn wmemcount_portable(needle: Self, haystack: &[Self]) -> usize;
+    #[doc(hidden)]
+    #[cfg(feature = "unstable")]
+    fn wmemchr_any_portable(needles: &[Self], haystack: &[Self]) -> Option<usize>;
+
+    #[doc(hidden)]
+    #[inline(always)]
+    fn wmemchr_any(needles: &[Self], haystack: &[Self]) -> Option<usize> {
+        #[cfg(all(
+            feature = "unstable",
+            any(miri, not(any(target_arch = "x86", target_arch = "x86_64")))
+        ))]
+        if portable::backend() == Backend::Portable {
+            return Self::wmemchr_any_portable(needles, haystack);
+        }
+
+        naive::wmemchr_any(needles, haystack)
+    }
+    #[doc(hidden)]
+    #[inline(always)]
+    fn wmemchr_range(start: Self, end: Self, haystack: &[Self]) -> Option<usize> {
+        naive::wmemchr_range(start..=end, haystack)
+    }
+}
+
+/// A trait for types that are represented in memory by a [`Wide`] type.
+///
+/// This allows slices of newtypes over wide characters to be searched
+/// directly. Every [`Wide`] type is trivially represented by itself.
+///
+/// Searches compare the underlying representation, rather than using any
+/// [`PartialEq`] implementation of the implementing type.
+///
+/// # Safety
+///
+/// Implementing types must have the same size, alignment and valid bit
+/// patterns as [`Repr`], such as a `#[repr(transparent)]` newtype over
+/// [`Repr`].
+///
+/// [`Repr`]: WideRepr::Repr
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::{wmemchr, WideRepr};
+///
+/// #[derive(Clone, Copy)]
+/// #[repr(transparent)]
+/// struct Utf16Unit(u16);
+///
+/// unsafe impl WideRepr for Utf16Unit {
+///     type Repr = u16;
+/// }
+///
+/// let haystack = [Utf16Unit(0x66), Utf16Unit(0x6f), Utf16Unit(0x6f)];
+///
+/// assert_eq!(wmemchr(Utf16Unit(0x6f), &haystack), Some(1));
+/// assert_eq!(wmemchr(Utf16Unit(0x7a), &haystack), None);
+/// ```
+pub unsafe trait WideRepr: Copy + 'static {
+    /// The wide character type used to represent `Self`.
+    type Repr: Wide;
+}
+
+unsafe impl<T: Wide> WideRepr for T {
+    type Repr = T;
+}
+
+/// Converts a value into its wide character representation.
+#[inline(always)]
+pub(crate) fn to_repr<T: WideRepr>(value: T) -> T::Repr {
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<T::Repr>());
+
+    // SAFETY: By the contract of `WideRepr`, `T` has the same layout and
+    //         valid bit patterns as `T::Repr`.
+    unsafe { mem::transmute_copy(&value) }
+}
+
+/// Converts a slice into a slice of its wide character representation.
+#[inline(always)]
+pub(crate) fn as_repr_slice<T: WideRepr>(slice: &[T]) -> &[T::Repr] {
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<T::Repr>());
+    debug_assert_eq!(mem::align_of::<T>(), mem::align_of::<T::Repr>());
+
+    // SAFETY: By the contract of `WideRepr`, `T` has the same layout and
+    //         valid bit patterns as `T::Repr`.
+    unsafe { slice::from_raw_parts(slice.as_ptr() as *const T::Repr, slice.len()) }
+}
+
+/// Converts a mutable slice into a slice of its wide character representation.
+#[inline(always)]
+pub(crate) fn as_repr_slice_mut<T: WideRepr>(slice: &mut [T]) -> &mut [T::Repr] {
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<T::Repr>());
+    debug_assert_eq!(mem::align_of::<T>(), mem::align_of::<T::Repr>());
+
+    // SAFETY: By the contract of `WideRepr`, `T` has the same layout and
+    //         valid bit patterns as `T::Repr`.
+    unsafe { slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut T::Repr, slice.len()) }
 }
 
 macro_rules! impl_wide {
-    ($($ty:ty),*) => {
+    // The SSE4.2 string instructions, and the AVX2 kernels, support sets and
+    // ranges of 16-bit elements.
+    (@set u16) => {
+        #[inline(always)]
+        #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+        fn wmemchr_any(needles: &[u16], haystack: &[u16]) -> Option<usize> {
+            // SAFETY: `u16` and `i16` have the same layout and valid bit patterns.
+            let (needles, haystack) = unsafe {
+                (
+                    slice::from_raw_parts(needles.as_ptr() as *const i16, needles.len()),
+                    slice::from_raw_parts(haystack.as_ptr() as *const i16, haystack.len()),
+                )
+            };
+            x86_64::wmemchr_any16(needles, haystack)
+        }
+        #[inline(always)]
+        #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+        fn wmemchr_range(start: u16, end: u16, haystack: &[u16]) -> Option<usize> {
+            x86_64::wmemchr_range16(start, end, haystack)
+        }
+    };
+    (@set i16) => {
+        #[inline(always)]
+        #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+        fn wmemchr_any(needles: &[i16], haystack: &[i16]) -> Option<usize> {
+            x86_64::wmemchr_any16(needles, haystack)
+        }
+        #[inline(always)]
+        #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+        fn wmemchr_range(start: i16, end: i16, haystack: &[i16]) -> Option<usize> {
+            x86_64::wmemchr_range16_signed(start, end, haystack)
+        }
+    };
+    (@set $ty:ident) => {};
+    ($($ty:ident),*) => {
         $(
             impl private::Sealed for $ty {}
 
             impl Wide for $ty {
+                #[inline(always)]
+                fn registry() -> &'static Registry<$ty> {
+                    static REGISTRY: Registry<$ty> = Registry::new();
+                    &REGISTRY
+                }
+
+                #[inline]
+                fn backend() -> Backend {
+                    cfg_if::cfg_if! {
+                        if #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))] {
+                            <x86_64::Kernel as KernelFn<$ty>>::backend()
+                        } else if #[cfg(feature = "unstable")] {
+                            portable::backend()
+                        } else {
+                            <fallback::Kernel as KernelFn<$ty>>::backend()
+                        }
+                    }
+                }
+
                 #[inline(always)]
                 fn wmemchr_naive(needle: $ty, haystack: &[$ty]) -> Option<usize> {
-                    naive::Kernel::kernel(needle, haystack)
+                    const_fn::$ty::const_wmemchr(needle, haystack)
+                }
+                #[inline(always)]
+                fn wmemrchr_naive(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                    const_fn::$ty::const_wmemrchr(needle, haystack)
                 }
                 #[inline(always)]
-                fn wmemchr_fallback(needle: $ty, haystack: &[$ty]) -> Option<usize> {
-                    fallback::Kernel::kernel(needle, haystack)
+                unsafe fn wmemchr_fallback(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    fallback::Kernel::kernel(needle, haystack, len)
                 }
                 #[inline(always)]
-                #[cfg(all(not(miri), target_arch = "x86_64"))]
-                fn wmemchr_x86_64(needle: $ty, haystack: &[$ty]) -> Option<usize> {
-                    x86_64::Kernel::kernel(needle, haystack)
+                unsafe fn wmemrchr_fallback(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    fallback::Kernel::kernel_reverse(needle, haystack, len)
                 }
+                #[inline(always)]
+                unsafe fn wmemcount_fallback(needle: $ty, haystack: *const $ty, len: usize) -> usize {
+                    fallback::Kernel::kernel_count(needle, haystack, len)
+                }
+                #[inline(always)]
+                #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+                unsafe fn wmemchr_x86_64(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    x86_64::Kernel::kernel(needle, haystack, len)
+                }
+                #[inline(always)]
+                #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+                unsafe fn wmemrchr_x86_64(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    x86_64::Kernel::kernel_reverse(needle, haystack, len)
+                }
+                #[inline(always)]
+                #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+                unsafe fn wmemcount_x86_64(needle: $ty, haystack: *const $ty, len: usize) -> usize {
+                    x86_64::Kernel::kernel_count(needle, haystack, len)
+                }
+
+                #[inline(always)]
+                #[cfg(feature = "unstable")]
+                fn wmemchr_portable(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                    portable::Search::forward(needle, haystack)
+                }
+                #[inline(always)]
+                #[cfg(feature = "unstable")]
+                fn wmemrchr_portable(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                    portable::Search::reverse(needle, haystack)
+                }
+                #[inline(always)]
+                #[cfg(feature = "unstable")]
+                fn wmemcount_portable(needle: $ty, haystack: &[$ty]) -> usize {
+                    portable::Search::count(needle, haystack)
+                }
+                #[inline(always)]
+                #[cfg(feature = "unstable")]
+                fn wmemchr_any_portable(needles: &[$ty], haystack: &[$ty]) -> Option<usize> {
+                    portable::Search::any(needles, haystack)
+                }
+
+                impl_wide! { @set $ty }
             }
         )*
     };
 }
-impl_wide!(u16, u32, i16, i32);
+impl_wide!(u8, u16, u32, i8, i16, i32, char);
+
+/// A trait for wide character types whose byte order can be reversed.
+///
+/// This is implemented for all of the integer [`Wide`] types.
+pub trait SwapBytes: Wide {
+    #[doc(hidden)]
+    fn swap_bytes(self) -> Self;
+    #[doc(hidden)]
+    fn swap_endianness(data: &mut [Self]);
+}
+
+macro_rules! impl_swap_bytes {
+    ($($ty:ty),*) => {
+        $(
+            impl SwapBytes for $ty {
+                #[inline(always)]
+                fn swap_bytes(self) -> $ty {
+                    self
+                }
+                #[inline(always)]
+                fn swap_endianness(_data: &mut [$ty]) {}
+            }
+        )*
+    };
+    ($($ty:ty => $ity:ty, $kernel:ident;)*) => {
+        $(
+            impl SwapBytes for $ty {
+                #[inline(always)]
+                fn swap_bytes(self) -> $ty {
+                    <$ty>::swap_bytes(self)
+                }
+                #[inline(always)]
+                fn swap_endianness(data: &mut [$ty]) {
+                    cfg_if::cfg_if! {
+                        if #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))] {
+                            unsafe { x86_64::$kernel(data.as_mut_ptr() as *mut $ity, data.len()) }
+                        } else {
+                            for c in data {
+                                *c = c.swap_bytes();
+                            }
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+impl_swap_bytes!(u8, i8);
+impl_swap_bytes! {
+    u16 => i16, swap_endianness16;
+    i16 => i16, swap_endianness16;
+    u32 => i32, swap_endianness32;
+    i32 => i32, swap_endianness32;
+}
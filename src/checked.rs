@@ -0,0 +1,82 @@
+//! Cross-validation of the public search functions against the
+//! [`naive`](crate::naive) implementation, or a scalar loop for strings, with
+//! the `checked` feature.
+
+use core::fmt;
+
+use crate::backend::{backend, Backend};
+use crate::char::Wide;
+use crate::sweep::ALIGN;
+
+/// Checks that the result of a search agrees with the result of the naive
+/// implementation.
+///
+/// `T` is the wide character type whose backend ran the search, and `custom`
+/// is whether a custom kernel ran it instead.
+///
+/// # Panics
+///
+/// Panics with the backend, needle, length and alignment of the haystack if
+/// the results disagree.
+#[inline(always)]
+pub(crate) fn check<T: Wide, N: fmt::Debug, H, R: fmt::Debug + PartialEq>(
+    op: &str,
+    custom: bool,
+    needle: N,
+    haystack: &[H],
+    actual: R,
+    expected: R,
+) {
+    if actual != expected {
+        let backend = if custom {
+            "custom"
+        } else {
+            backend::<T>().name()
+        };
+
+        disagree(op, backend, needle, haystack, actual, expected);
+    }
+}
+
+/// Checks that the result of a search by the kernels of `backend` agrees with
+/// the result of the naive implementation.
+///
+/// # Panics
+///
+/// Panics as for [`check`] if the results disagree.
+#[inline(always)]
+pub(crate) fn check_backend<N: fmt::Debug, H, R: fmt::Debug + PartialEq>(
+    op: &str,
+    backend: Backend,
+    needle: N,
+    haystack: &[H],
+    actual: R,
+    expected: R,
+) {
+    if actual != expected {
+        disagree(op, backend.name(), needle, haystack, actual, expected);
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn disagree<N: fmt::Debug, H, R: fmt::Debug>(
+    op: &str,
+    backend: &str,
+    needle: N,
+    haystack: &[H],
+    actual: R,
+    expected: R,
+) -> ! {
+    panic!(
+        "{} disagrees with naive on the {} backend: returned {:?}, expected {:?}, \
+         needle {:?}, len {}, alignment {}",
+        op,
+        backend,
+        actual,
+        expected,
+        needle,
+        haystack.len(),
+        haystack.as_ptr() as usize % ALIGN,
+    );
+}
@@ -0,0 +1,132 @@
+//! Search functions that can be called in `const` contexts, such as to build
+//! lookup tables at compile time.
+//!
+//! Trait methods cannot be called in a `const fn`, so there is a module of
+//! functions for each [`Wide`](crate::Wide) type. These search one element at
+//! a time, and [`naive::wmemchr`] and [`naive::wmemrchr`] are built on them, as
+//! are the searches of unaligned haystacks when there is no SIMD kernel for
+//! them.
+//!
+//! [`naive::wmemchr`]: crate::naive::wmemchr
+//! [`naive::wmemrchr`]: crate::naive::wmemrchr
+//!
+//! # Examples
+//!
+//! Basic usage:
+//!
+//! ```
+//! use wmemchr::const_fn::u16::{const_wmemchr, const_wmemmem};
+//!
+//! const HAYSTACK: &[u16] = &[0x66, 0x6f, 0x6f, 0x20, 0x62, 0x61, 0x72];
+//!
+//! const SPACE: Option<usize> = const_wmemchr(0x20, HAYSTACK);
+//! const BAR: Option<usize> = const_wmemmem(&[0x62, 0x61, 0x72], HAYSTACK);
+//!
+//! assert_eq!(SPACE, Some(3));
+//! assert_eq!(BAR, Some(4));
+//! ```
+
+macro_rules! const_fn {
+    ($($ty:ident: $doc:literal;)*) => {
+        $(
+            #[doc = $doc]
+            pub mod $ty {
+                /// Returns the index of the first occurrence of a wide
+                /// character in a slice, or [`None`] if the character is not
+                /// found.
+                #[inline]
+                pub const fn const_wmemchr(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                    let mut i = 0;
+                    while i < haystack.len() {
+                        if haystack[i] == needle {
+                            return Some(i);
+                        }
+                        i += 1;
+                    }
+                    None
+                }
+
+                /// Returns the index of the last occurrence of a wide
+                /// character in a slice, or [`None`] if the character is not
+                /// found.
+                #[inline]
+                pub const fn const_wmemrchr(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                    let mut i = haystack.len();
+                    while i > 0 {
+                        i -= 1;
+                        if haystack[i] == needle {
+                            return Some(i);
+                        }
+                    }
+                    None
+                }
+
+                /// Returns the index of the first occurrence of a slice of wide
+                /// characters in a slice, or [`None`] if it is not found.
+                ///
+                /// An empty needle is found at the start of the haystack.
+                #[inline]
+                pub const fn const_wmemmem(needle: &[$ty], haystack: &[$ty]) -> Option<usize> {
+                    if needle.len() > haystack.len() {
+                        return None;
+                    }
+
+                    let mut i = 0;
+                    while i <= haystack.len() - needle.len() {
+                        let mut j = 0;
+                        while j < needle.len() && haystack[i + j] == needle[j] {
+                            j += 1;
+                        }
+                        if j == needle.len() {
+                            return Some(i);
+                        }
+                        i += 1;
+                    }
+                    None
+                }
+
+                const_fn!(@bytes $ty);
+            }
+        )*
+    };
+    (@bytes u8) => {};
+    (@bytes i8) => {};
+    (@bytes char) => {};
+    (@bytes $ty:ident) => {
+        /// Returns the index of the first occurrence of a wide character in a
+        /// byte slice of native endian code units, or [`None`] if the
+        /// character is not found.
+        ///
+        /// The byte slice does not need to be aligned, and any trailing bytes
+        /// that do not form a whole code unit are ignored.
+        #[inline]
+        pub const fn const_wmemchr_ne_bytes(needle: $ty, haystack: &[u8]) -> Option<usize> {
+            const SIZE: usize = core::mem::size_of::<$ty>();
+
+            let mut i = 0;
+            while i < haystack.len() / SIZE {
+                let mut bytes = [0; SIZE];
+                let mut j = 0;
+                while j < SIZE {
+                    bytes[j] = haystack[i * SIZE + j];
+                    j += 1;
+                }
+                if $ty::from_ne_bytes(bytes) == needle {
+                    return Some(i);
+                }
+                i += 1;
+            }
+            None
+        }
+    };
+}
+
+const_fn! {
+    u8: "Search functions for slices of [`u8`](prim@u8).";
+    u16: "Search functions for slices of [`u16`](prim@u16).";
+    u32: "Search functions for slices of [`u32`](prim@u32).";
+    i8: "Search functions for slices of [`i8`](prim@i8).";
+    i16: "Search functions for slices of [`i16`](prim@i16).";
+    i32: "Search functions for slices of [`i32`](prim@i32).";
+    char: "Search functions for slices of [`char`](prim@char).";
+}
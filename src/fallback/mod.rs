@@ -1,8 +1,10 @@
 //! Pure Rust platform independent implementation designed for speed.
 
-use core::mem;
+use core::{mem, ptr};
 
-use crate::char::{KernelFn, Wide};
+use crate::backend::Backend;
+use crate::char::{as_repr_slice, to_repr, KernelFn, SwapBytes, Wide, WideRepr};
+use crate::vector;
 
 mod packed;
 
@@ -25,168 +27,258 @@ use self::packed::{simd_eq, NonZeroPacked, Pack, Packed};
 /// assert_eq!(wmemchr(wch!(u16, 'z'), haystack), None);
 /// ```
 #[inline(always)]
-pub fn wmemchr<T: Wide>(needle: T, haystack: &[T]) -> Option<usize> {
-    T::wmemchr_fallback(needle, haystack)
+pub fn wmemchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    // SAFETY: A slice is valid for reads of its length.
+    let pos = unsafe { T::Repr::wmemchr_fallback(needle, haystack.as_ptr(), haystack.len()) };
+
+    #[cfg(feature = "checked")]
+    crate::checked::check_backend(
+        concat!(module_path!(), "::wmemchr"),
+        Backend::Fallback,
+        needle,
+        haystack,
+        pos,
+        crate::naive::wmemchr(needle, haystack),
+    );
+
+    pos
+}
+
+/// Returns the index of the first occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// This searches a haystack of opposite endianness in place, by swapping the
+/// byte order of the needle.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::fallback::wmemchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemchr_swapped(0x006f, &haystack), Some(1));
+/// assert_eq!(wmemchr_swapped(0x007a, &haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the index of the last occurrence of a wide character in a slice,
+/// or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::fallback::wmemrchr;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemrchr(wch!(u16, 'o'), haystack), Some(2));
+/// assert_eq!(wmemrchr(wch!(u16, 'z'), haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemrchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    // SAFETY: A slice is valid for reads of its length.
+    let pos = unsafe { T::Repr::wmemrchr_fallback(needle, haystack.as_ptr(), haystack.len()) };
+
+    #[cfg(feature = "checked")]
+    crate::checked::check_backend(
+        concat!(module_path!(), "::wmemrchr"),
+        Backend::Fallback,
+        needle,
+        haystack,
+        pos,
+        crate::naive::wmemrchr(needle, haystack),
+    );
+
+    pos
+}
+
+/// Returns the number of occurrences of a wide character in a slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::fallback::wmemcount;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemcount(wch!(u16, 'o'), haystack), 2);
+/// assert_eq!(wmemcount(wch!(u16, 'z'), haystack), 0);
+/// ```
+#[inline(always)]
+pub fn wmemcount<T: WideRepr>(needle: T, haystack: &[T]) -> usize {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    // SAFETY: A slice is valid for reads of its length.
+    let count = unsafe { T::Repr::wmemcount_fallback(needle, haystack.as_ptr(), haystack.len()) };
+
+    #[cfg(feature = "checked")]
+    crate::checked::check_backend(
+        concat!(module_path!(), "::wmemcount"),
+        Backend::Fallback,
+        needle,
+        haystack,
+        count,
+        crate::naive::wmemcount(needle, haystack),
+    );
+
+    count
+}
+
+/// Returns the index of the last occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::fallback::wmemrchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemrchr_swapped(0x006f, &haystack), Some(2));
+/// assert_eq!(wmemrchr_swapped(0x007a, &haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemrchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemrchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the number of occurrences of a byte swapped wide character in a
+/// slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::fallback::wmemcount_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemcount_swapped(0x006f, &haystack), 2);
+/// assert_eq!(wmemcount_swapped(0x007a, &haystack), 0);
+/// ```
+#[inline(always)]
+pub fn wmemcount_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> usize
+where
+    T::Repr: SwapBytes,
+{
+    wmemcount(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
 }
 
 pub(crate) struct Kernel;
 
 impl<T: Pack> KernelFn<T> for Kernel {
-    fn kernel(needle: T, haystack: &[T]) -> Option<usize> {
-        const VECTOR_SIZE: usize = mem::size_of::<Packed>();
-        const VECTOR_ALIGN: usize = VECTOR_SIZE - 1;
-
-        const LOOP_SIZE: usize = 4 * VECTOR_SIZE;
-        let loop_elements = 4 * T::LANES;
-
-        let start = haystack.as_ptr();
-        let mut ptr = start;
-
-        unsafe {
-            let end = start.add(haystack.len());
-
-            debug_assert!(start <= end);
-
-            // If haystack length is less than number of elements in a packed vector,
-            // then do a simple forward search.
-            if haystack.len() < T::LANES {
-                while ptr < end {
-                    if *ptr == needle {
-                        return Some(ptr.offset_from(start) as usize);
-                    }
-                    ptr = ptr.add(1);
-                }
-                return None;
-            }
-
-            debug_assert!(end.offset_from(start) as usize >= T::LANES);
-
-            // Broadcast the needle across the elements of the vector.
-            let v_needle = needle.broadcast();
-
-            if let Some(pos) = forward_search_unaligned(start, end, ptr, v_needle) {
-                return Some(pos);
-            }
-
-            // Align `ptr` to improve read performance in loop.
-            // This calculation is based on byte pointer, and not the scaled addition.
-            ptr = {
-                let align_offset = VECTOR_SIZE - ((start as usize) & VECTOR_ALIGN);
-                (start as *const u8).add(align_offset) as *const T
-            };
-
-            // The pointer will advance at least one element and at most by the
-            // number of elements in one vector.
-            debug_assert!(start < ptr);
-            debug_assert!(ptr.offset_from(start) as usize <= T::LANES);
-
-            if let Some(loop_end) = (end as usize).checked_sub(LOOP_SIZE) {
-                while (ptr as usize) <= loop_end {
-                    debug_assert_eq!((ptr as usize) % VECTOR_SIZE, 0);
-
-                    let p = ptr as *const Packed;
-
-                    // Load 4 vectors of characters.
-                    let a = *p;
-                    let b = *p.add(1);
-                    let c = *p.add(2);
-                    let d = *p.add(3);
-
-                    // Look for needle in vectors.
-                    let eq_a = simd_eq::<T>(a, v_needle);
-                    let eq_b = simd_eq::<T>(b, v_needle);
-                    let eq_c = simd_eq::<T>(c, v_needle);
-                    let eq_d = simd_eq::<T>(d, v_needle);
-
-                    // Determine if any vectors contained the needle.
-                    let or_ab = eq_a | eq_b;
-                    let or_cd = eq_c | eq_d;
-                    let or = or_ab | or_cd;
-
-                    // If any vector contains the needle, we will search for it in each vector.
-                    if or != 0 {
-                        // Keep track of the offset from the start of the haystack.
-                        let mut offset = ptr.offset_from(start) as usize;
-
-                        if let Some(mask) = NonZeroPacked::new(eq_a) {
-                            return Some(offset + forward_pos::<T>(mask));
-                        }
-                        offset += T::LANES;
-
-                        if let Some(mask) = NonZeroPacked::new(eq_b) {
-                            return Some(offset + forward_pos::<T>(mask));
-                        }
-                        offset += T::LANES;
-
-                        if let Some(mask) = NonZeroPacked::new(eq_c) {
-                            return Some(offset + forward_pos::<T>(mask));
-                        }
-                        offset += T::LANES;
-
-                        debug_assert_ne!(eq_d, 0);
-                        let mask = NonZeroPacked::new_unchecked(eq_d);
-                        return Some(offset + forward_pos::<T>(mask));
-                    }
-
-                    ptr = ptr.add(loop_elements);
-                }
-            }
-
-            if let Some(loop_end) = (end as usize).checked_sub(VECTOR_SIZE) {
-                while (ptr as usize) <= loop_end {
-                    debug_assert_eq!((ptr as usize) % VECTOR_SIZE, 0);
-
-                    let chunk = *(ptr as *const Packed);
-                    let eq = simd_eq::<T>(chunk, v_needle);
-
-                    if let Some(mask) = NonZeroPacked::new(eq) {
-                        let offset = ptr.offset_from(start) as usize;
-                        return Some(offset + forward_pos::<T>(mask));
-                    }
-
-                    ptr = ptr.add(T::LANES);
-                }
-            }
-
-            // Invariant: `0 <= end - ptr < T::LANES`.
-
-            // We can search the remaining elements by shifting `ptr` back and doing an
-            // unaligned forward search.
-
-            if ptr < end {
-                let remaining = end.offset_from(ptr) as usize;
-
-                debug_assert!(remaining < T::LANES);
-                ptr = ptr.sub(T::LANES - remaining);
-                debug_assert_eq!(end.offset_from(ptr) as usize, T::LANES);
-
-                return forward_search_unaligned(start, end, ptr, v_needle);
-            }
-
-            None
+    unsafe fn kernel(needle: T, haystack: *const T, len: usize) -> Option<usize> {
+        // If haystack length is less than number of elements in a packed vector,
+        // then search it with at most two overlapping loads.
+        if len < T::LANES {
+            return wmemchr_tiny(needle, haystack, len);
         }
+
+        vector::wmemchr::<T, Packed>(needle, haystack, len, None)
+    }
+
+    unsafe fn kernel_reverse(needle: T, haystack: *const T, len: usize) -> Option<usize> {
+        vector::wmemrchr::<T, Packed>(needle, haystack, len)
     }
-}
 
-#[inline]
-unsafe fn forward_search_unaligned<T: Pack>(
-    start: *const T,
-    end: *const T,
-    ptr: *const T,
-    v_needle: Packed,
-) -> Option<usize> {
-    debug_assert!(start <= ptr);
-    debug_assert!(end.offset_from(ptr) as usize >= T::LANES);
-
-    let chunk = (ptr as *const Packed).read_unaligned();
-    let eq = simd_eq::<T>(chunk, v_needle);
-
-    if let Some(mask) = NonZeroPacked::new(eq) {
-        let offset = ptr.offset_from(start) as usize;
-        Some(offset + forward_pos::<T>(mask))
-    } else {
-        None
+    unsafe fn kernel_count(needle: T, haystack: *const T, len: usize) -> usize {
+        vector::wmemcount::<T, Packed>(needle, haystack, len)
     }
+
+    fn backend() -> Backend {
+        Backend::Fallback
+    }
+}
+
+/// Returns the number of elements before the null terminator of a string, or
+/// `maxlen` if there is none within it, with aligned loads.
+#[cfg(not(miri))]
+#[inline(always)]
+pub(crate) unsafe fn wcsnlen<T: Pack>(s: *const T, maxlen: usize) -> usize {
+    vector::wcsnlen::<T, Packed>(s, maxlen)
+}
+
+/// Returns the index of the first occurrence of `needle` in a string, or of
+/// its null terminator, with aligned loads.
+#[cfg(not(miri))]
+#[inline(always)]
+pub(crate) unsafe fn wcschr<T: Pack>(s: *const T, needle: T) -> usize {
+    vector::wcschr::<T, Packed>(s, needle)
+}
+
+/// Searches a haystack shorter than a [`Packed`], with at most two overlapping
+/// loads, and without branching on the contents of the haystack.
+#[inline(always)]
+unsafe fn wmemchr_tiny<T: Pack>(needle: T, haystack: *const T, len: usize) -> Option<usize> {
+    let size = len * T::SIZE;
+    let bytes = haystack as *const u8;
+
+    debug_assert!(size < mem::size_of::<Packed>());
+
+    // Fill the lanes that are not loaded with a value other than the needle.
+    let v_needle = needle.broadcast();
+    let mut chunk = (!v_needle).to_ne_bytes();
+
+    // Load the first and last `width` bytes of the haystack into the chunk,
+    // which overlap unless the haystack is exactly `2 * width` bytes. The
+    // widths are constant in each arm, so that the copies are simple loads.
+    let width = match size {
+        0 => return None,
+        1 => load_tiny(bytes, size, 1, &mut chunk),
+        2..=3 => load_tiny(bytes, size, 2, &mut chunk),
+        _ => load_tiny(bytes, size, 4, &mut chunk),
+    };
+
+    let eq = simd_eq::<T>(Packed::from_ne_bytes(chunk), v_needle);
+
+    NonZeroPacked::new(eq).map(|mask| {
+        // Shift the matches in the last load to their offset in the haystack.
+        let lanes = width / T::SIZE;
+        let pos = forward_pos::<T>(mask);
+        if pos < lanes {
+            pos
+        } else {
+            pos + len - 2 * lanes
+        }
+    })
+}
+
+/// Copies the first and last `width` bytes of a haystack of `size` bytes into
+/// the start of `chunk`, returning `width`.
+#[inline(always)]
+unsafe fn load_tiny(bytes: *const u8, size: usize, width: usize, chunk: &mut [u8]) -> usize {
+    debug_assert!(width <= size && size < 2 * width);
+    debug_assert!(2 * width <= chunk.len());
+
+    ptr::copy_nonoverlapping(bytes, chunk.as_mut_ptr(), width);
+    ptr::copy_nonoverlapping(
+        bytes.add(size - width),
+        chunk.as_mut_ptr().add(width),
+        width,
+    );
+    width
 }
 
 #[inline(always)]
@@ -1,5 +1,7 @@
 use core::mem;
 
+use crate::vector::Vector;
+
 /// A trait for types that have a constant size known at compile time.
 pub trait Sized: core::marker::Sized {
     /// The number of bytes used to represent the type in memory.
@@ -12,7 +14,7 @@ impl<T: core::marker::Sized> Sized for T {}
 
 // TODO: Specialise each wide character type for different pointer widths.
 cfg_if::cfg_if! {
-    if #[cfg(any(target_pointer_width = "8", target_pointer_width = "16"))] {
+    if #[cfg(target_pointer_width = "16")] {
         // If usize is less than 32 bits, use a u32.
         type _Packed = u32;
         type _NonZeroPacked = core::num::NonZeroU32;
@@ -30,8 +32,6 @@ pub type NonZeroPacked = _NonZeroPacked;
 pub trait Pack: Sized + Copy + Eq + 'static {
     /// The number of lanes that the packed representation can hold.
     const LANES: usize = Packed::SIZE / Self::SIZE;
-    /// The bitmask used to align a packed pointer.
-    const ALIGN: usize = Self::LANES - 1;
 
     /// A packed representation of the the lowest bit.
     const LO: Packed;
@@ -66,11 +66,75 @@ macro_rules! impl_pack {
         }
     };
 }
+impl_pack!(i8, u8);
 impl_pack!(i16, u16);
 impl_pack!(i32, u32);
 
+impl Pack for char {
+    const LO: Packed = <u32 as Pack>::LO;
+    const HI: Packed = <u32 as Pack>::HI;
+
+    #[inline(always)]
+    fn broadcast(self) -> Packed {
+        // A `char` is represented in memory as its `u32` scalar value.
+        <u32 as Pack>::broadcast(self as u32)
+    }
+}
+
+/// Sets the highest bit of each lane in which `a` and `b` are equal.
+///
+/// Unlike the shorter `(x - LO) & !x & HI`, the borrow of an equal lane does
+/// not set the bit of the lane above it, so every lane of the mask is exact,
+/// as the reverse and count searches require.
 #[inline(always)]
 pub fn simd_eq<T: Pack>(a: Packed, b: Packed) -> Packed {
-    let xor = (a ^ b).to_le();
-    Packed::from_le(xor.wrapping_sub(<T as Pack>::LO) & !xor & <T as Pack>::HI)
+    let xor = a ^ b;
+    let low = !<T as Pack>::HI;
+
+    // The highest bit of each lane is set if any of its other bits are set,
+    // without carrying into the next lane.
+    let nonzero_low = (xor & low).wrapping_add(low);
+
+    !(nonzero_low | xor | low)
+}
+
+unsafe impl<T: Pack> Vector<T> for Packed {
+    const LANES: usize = <T as Pack>::LANES;
+    const MASK_STRIDE: usize = <T as Sized>::BITS;
+    // Only the highest bit of each lane is set.
+    const MASK_BITS: u32 = 1;
+
+    type Mask = Packed;
+
+    #[inline(always)]
+    unsafe fn splat(value: T) -> Packed {
+        value.broadcast()
+    }
+
+    #[inline(always)]
+    unsafe fn load_aligned(ptr: *const T) -> Packed {
+        *(ptr as *const Packed)
+    }
+
+    #[inline(always)]
+    unsafe fn load_unaligned(ptr: *const T) -> Packed {
+        (ptr as *const Packed).read_unaligned()
+    }
+
+    #[inline(always)]
+    unsafe fn cmpeq(self, other: Packed) -> Packed {
+        simd_eq::<T>(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn or(a: Packed, b: Packed) -> Packed {
+        a | b
+    }
+
+    #[inline(always)]
+    unsafe fn movemask(mask: Packed) -> u64 {
+        // The lanes of the mask are in memory order, so the first element is in
+        // the lowest bits once the mask is little endian.
+        mask.to_le() as u64
+    }
 }
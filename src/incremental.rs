@@ -0,0 +1,154 @@
+//! Substring searches of input that arrives in chunks.
+
+use crate::char::{as_repr_slice, WideRepr};
+
+/// A push-style searcher for a substring of wide characters, in input that is
+/// fed to it in chunks, such as fragments of a network payload.
+///
+/// Matches are reported as absolute offsets from the start of the input, and
+/// are found even if they straddle two or more chunks. Every occurrence is
+/// found, including overlapping occurrences.
+///
+/// No input is copied between chunks. The only state carried over is the
+/// length of the longest suffix of the input that is a prefix of the needle.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::IncrementalSearcher;
+///
+/// let chunks: [&[u16]; 2] = [wch!(u16, "GET / HTTP/1.1\r"), wch!(u16, "\nHost: a\r\n")];
+///
+/// let mut searcher = IncrementalSearcher::new(wch!(u16, "\r\n"));
+/// let mut offsets = Vec::new();
+///
+/// for chunk in &chunks {
+///     searcher.feed(chunk, |offset| offsets.push(offset));
+/// }
+///
+/// assert_eq!(offsets, [14, 23]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct IncrementalSearcher<T: WideRepr> {
+    needle: Box<[T::Repr]>,
+    /// The length of the longest proper border of each prefix of the needle,
+    /// indexed by the length of the prefix.
+    borders: Box<[usize]>,
+    /// The length of the longest suffix of the input that is a proper prefix
+    /// of the needle.
+    matched: usize,
+    /// The number of wide characters fed so far.
+    offset: u64,
+}
+
+impl<T: WideRepr> IncrementalSearcher<T> {
+    /// Creates a searcher for a substring of wide characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the needle is empty.
+    pub fn new(needle: &[T]) -> IncrementalSearcher<T> {
+        assert!(!needle.is_empty(), "the needle must not be empty");
+
+        let needle = as_repr_slice(needle);
+
+        // The Knuth-Morris-Pratt failure function.
+        let mut borders = vec![0; needle.len() + 1];
+        let mut k = 0;
+        for i in 1..needle.len() {
+            while k > 0 && needle[i] != needle[k] {
+                k = borders[k];
+            }
+            if needle[i] == needle[k] {
+                k += 1;
+            }
+            borders[i + 1] = k;
+        }
+
+        IncrementalSearcher {
+            needle: needle.into(),
+            borders: borders.into_boxed_slice(),
+            matched: 0,
+            offset: 0,
+        }
+    }
+
+    /// Returns the number of wide characters fed so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Resets the searcher, so that the next chunk fed starts at offset zero.
+    pub fn reset(&mut self) {
+        self.matched = 0;
+        self.offset = 0;
+    }
+
+    /// Feeds the next chunk of input to the searcher, calling `on_match` with
+    /// the offset of each match that ends within the chunk, in order.
+    pub fn feed<F: FnMut(u64)>(&mut self, chunk: &[T], mut on_match: F) {
+        let chunk = as_repr_slice(chunk);
+        let n = self.needle.len();
+        let base = self.offset;
+
+        // Matches that start in earlier chunks end within the first `n - 1`
+        // wide characters of the chunk, and are found by extending the prefix
+        // of the needle matched so far.
+        let head = chunk.len().min(n - 1);
+        let mut state = self.matched;
+        for (i, &c) in chunk[..head].iter().enumerate() {
+            state = self.step(state, c);
+            if state == n {
+                on_match(base + (i + 1) as u64 - n as u64);
+                state = self.borders[n];
+            }
+        }
+
+        if head == chunk.len() {
+            self.matched = state;
+            self.offset += chunk.len() as u64;
+            return;
+        }
+
+        // Matches within the chunk are found with the search kernels, by
+        // searching for the first wide character of the needle.
+        let (&first, rest) = self.needle.split_first().unwrap();
+        let end = chunk.len() - rest.len();
+        let mut pos = 0;
+        while let Some(i) = crate::wmemchr(first, &chunk[pos..end]) {
+            let start = pos + i;
+            if chunk[start + 1..start + n] == *rest {
+                on_match(base + start as u64);
+            }
+            pos = start + 1;
+        }
+
+        // The prefix of the needle matched at the end of the chunk is within
+        // its last `n - 1` wide characters, which cannot contain a match.
+        let mut state = 0;
+        for &c in &chunk[chunk.len() - (n - 1)..] {
+            state = self.step(state, c);
+        }
+
+        self.matched = state;
+        self.offset += chunk.len() as u64;
+    }
+
+    /// Returns the length of the prefix of the needle matched after extending
+    /// a matched prefix of length `state` with `c`.
+    #[inline]
+    fn step(&self, mut state: usize, c: T::Repr) -> usize {
+        loop {
+            if self.needle[state] == c {
+                return state + 1;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.borders[state];
+        }
+    }
+}
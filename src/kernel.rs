@@ -0,0 +1,282 @@
+//! Custom kernels, registered at runtime in place of the crate's kernels.
+
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::char::Wide;
+use crate::naive;
+use crate::sweep::sweep;
+
+/// A set of search functions over slices of `T`, which can replace the crate's
+/// kernels in the search functions.
+///
+/// Only [`wmemchr`](Kernel::wmemchr) must be implemented, the other operations
+/// default to the crate's own kernels.
+///
+/// The indices returned by a registered kernel are checked to be within the
+/// haystack before they are used, so that the raw pointer searches remain
+/// sound with a kernel that returns an index out of bounds.
+///
+/// # Panics
+///
+/// The search functions panic if a registered kernel returns an index out of
+/// bounds of the haystack.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::{register_kernel, unregister_kernel, wmemchr, Kernel};
+///
+/// struct Scalar;
+///
+/// impl Kernel<u32> for Scalar {
+///     fn wmemchr(needle: u32, haystack: &[u32]) -> Option<usize> {
+///         haystack.iter().position(|&c| c == needle)
+///     }
+/// }
+///
+/// register_kernel::<u32, Scalar>();
+/// assert_eq!(wmemchr(0x6f, &[0x66, 0x6f, 0x6f]), Some(1));
+/// unregister_kernel::<u32>();
+/// ```
+pub trait Kernel<T: Wide> {
+    /// Returns the index of the first occurrence of a wide character in a
+    /// slice, or [`None`] if the character is not found.
+    fn wmemchr(needle: T, haystack: &[T]) -> Option<usize>;
+
+    /// Returns the index of the last occurrence of a wide character in a
+    /// slice, or [`None`] if the character is not found.
+    #[inline]
+    fn wmemrchr(needle: T, haystack: &[T]) -> Option<usize> {
+        // SAFETY: A slice is valid for reads of its length.
+        unsafe { crate::builtin_wmemrchr(needle, haystack.as_ptr(), haystack.len()) }
+    }
+
+    /// Returns the number of occurrences of a wide character in a slice.
+    #[inline]
+    fn wmemcount(needle: T, haystack: &[T]) -> usize {
+        // SAFETY: A slice is valid for reads of its length.
+        unsafe { crate::builtin_wmemcount(needle, haystack.as_ptr(), haystack.len()) }
+    }
+
+    /// Returns the index of the first wide character in a slice that is equal
+    /// to any of the needles, or [`None`] if none are found.
+    #[inline]
+    fn wmemchr_any(needles: &[T], haystack: &[T]) -> Option<usize> {
+        T::wmemchr_any(needles, haystack)
+    }
+
+    /// Returns the index of the first wide character in a slice that is within
+    /// the inclusive range from `start` to `end`, or [`None`] if none are found.
+    ///
+    /// The range is empty if `start > end`.
+    #[inline]
+    fn wmemchr_range(start: T, end: T, haystack: &[T]) -> Option<usize> {
+        T::wmemchr_range(start, end, haystack)
+    }
+}
+
+type FnRaw = *mut ();
+
+type WmemchrFn<T> = fn(T, &[T]) -> Option<usize>;
+type WmemcountFn<T> = fn(T, &[T]) -> usize;
+type WmemchrAnyFn<T> = fn(&[T], &[T]) -> Option<usize>;
+type WmemchrRangeFn<T> = fn(T, T, &[T]) -> Option<usize>;
+
+/// The functions of the kernel registered for a wide character type.
+#[doc(hidden)]
+pub struct Registry<T> {
+    wmemchr: AtomicPtr<()>,
+    wmemrchr: AtomicPtr<()>,
+    wmemcount: AtomicPtr<()>,
+    wmemchr_any: AtomicPtr<()>,
+    wmemchr_range: AtomicPtr<()>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Registry<T> {
+    pub(crate) const fn new() -> Registry<T> {
+        Registry {
+            wmemchr: AtomicPtr::new(ptr::null_mut()),
+            wmemrchr: AtomicPtr::new(ptr::null_mut()),
+            wmemcount: AtomicPtr::new(ptr::null_mut()),
+            wmemchr_any: AtomicPtr::new(ptr::null_mut()),
+            wmemchr_range: AtomicPtr::new(ptr::null_mut()),
+            marker: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn wmemchr(&self) -> Option<WmemchrFn<T>> {
+        let f = self.wmemchr.load(Ordering::Relaxed);
+        // SAFETY: Only null or a `WmemchrFn<T>` is ever stored.
+        (!f.is_null()).then(|| unsafe { mem::transmute::<FnRaw, WmemchrFn<T>>(f) })
+    }
+
+    #[inline(always)]
+    pub(crate) fn wmemrchr(&self) -> Option<WmemchrFn<T>> {
+        let f = self.wmemrchr.load(Ordering::Relaxed);
+        // SAFETY: Only null or a `WmemchrFn<T>` is ever stored.
+        (!f.is_null()).then(|| unsafe { mem::transmute::<FnRaw, WmemchrFn<T>>(f) })
+    }
+
+    #[inline(always)]
+    pub(crate) fn wmemcount(&self) -> Option<WmemcountFn<T>> {
+        let f = self.wmemcount.load(Ordering::Relaxed);
+        // SAFETY: Only null or a `WmemcountFn<T>` is ever stored.
+        (!f.is_null()).then(|| unsafe { mem::transmute::<FnRaw, WmemcountFn<T>>(f) })
+    }
+
+    #[inline(always)]
+    pub(crate) fn wmemchr_any(&self) -> Option<WmemchrAnyFn<T>> {
+        let f = self.wmemchr_any.load(Ordering::Relaxed);
+        // SAFETY: Only null or a `WmemchrAnyFn<T>` is ever stored.
+        (!f.is_null()).then(|| unsafe { mem::transmute::<FnRaw, WmemchrAnyFn<T>>(f) })
+    }
+
+    #[inline(always)]
+    pub(crate) fn wmemchr_range(&self) -> Option<WmemchrRangeFn<T>> {
+        let f = self.wmemchr_range.load(Ordering::Relaxed);
+        // SAFETY: Only null or a `WmemchrRangeFn<T>` is ever stored.
+        (!f.is_null()).then(|| unsafe { mem::transmute::<FnRaw, WmemchrRangeFn<T>>(f) })
+    }
+}
+
+/// Registers a custom kernel, which is used by the search functions of the
+/// crate root for slices of `T`, and of types represented by `T`, until it is
+/// unregistered.
+///
+/// The functions of the kernel are replaced one at a time, so a search running
+/// concurrently with the registration may use either kernel.
+///
+/// See [`check_kernel`] to check the kernel before registering it.
+#[inline]
+pub fn register_kernel<T: Wide, K: Kernel<T>>() {
+    let registry = T::registry();
+
+    registry
+        .wmemchr
+        .store(K::wmemchr as WmemchrFn<T> as FnRaw, Ordering::Relaxed);
+    registry
+        .wmemrchr
+        .store(K::wmemrchr as WmemchrFn<T> as FnRaw, Ordering::Relaxed);
+    registry
+        .wmemcount
+        .store(K::wmemcount as WmemcountFn<T> as FnRaw, Ordering::Relaxed);
+    registry.wmemchr_any.store(
+        K::wmemchr_any as WmemchrAnyFn<T> as FnRaw,
+        Ordering::Relaxed,
+    );
+    registry.wmemchr_range.store(
+        K::wmemchr_range as WmemchrRangeFn<T> as FnRaw,
+        Ordering::Relaxed,
+    );
+}
+
+/// Unregisters the custom kernel for `T`, if any, so that the crate's kernels
+/// are used again.
+#[inline]
+pub fn unregister_kernel<T: Wide>() {
+    let registry = T::registry();
+
+    registry.wmemchr.store(ptr::null_mut(), Ordering::Relaxed);
+    registry.wmemrchr.store(ptr::null_mut(), Ordering::Relaxed);
+    registry.wmemcount.store(ptr::null_mut(), Ordering::Relaxed);
+    registry
+        .wmemchr_any
+        .store(ptr::null_mut(), Ordering::Relaxed);
+    registry
+        .wmemchr_range
+        .store(ptr::null_mut(), Ordering::Relaxed);
+}
+
+/// Checks that the index returned by the `op` operation of a registered kernel
+/// is within a haystack of `len` elements.
+///
+/// # Panics
+///
+/// Panics if the index is out of bounds.
+#[inline(always)]
+pub(crate) fn check_bounds(op: &str, pos: Option<usize>, len: usize) -> Option<usize> {
+    if let Some(pos) = pos {
+        if pos >= len {
+            out_of_bounds(op, pos, len);
+        }
+    }
+
+    pos
+}
+
+#[cold]
+#[inline(never)]
+fn out_of_bounds(op: &str, pos: usize, len: usize) -> ! {
+    panic!(
+        "registered {} kernel returned index {} out of bounds for a haystack of length {}",
+        op, pos, len,
+    );
+}
+
+/// Checks that every operation of a kernel agrees with the [`naive`]
+/// implementation, panicking if it does not.
+///
+/// Haystacks of every length up to 256 bytes are checked, starting at every
+/// element offset within 64 bytes, with the needle in every position, as well
+/// as huge haystacks of some lengths up to 1 KiB. With the `testing` feature on
+/// Unix platforms, haystacks are also placed directly before and after a guard
+/// page. This takes too long to run under Miri.
+///
+/// # Panics
+///
+/// Panics with the operation, length, offset and position on the first
+/// disagreement.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::{check_kernel, Kernel};
+///
+/// struct Scalar;
+///
+/// impl Kernel<u16> for Scalar {
+///     fn wmemchr(needle: u16, haystack: &[u16]) -> Option<usize> {
+///         haystack.iter().position(|&c| c == needle)
+///     }
+/// }
+///
+/// # #[cfg(not(miri))]
+/// check_kernel::<u16, Scalar>();
+/// ```
+pub fn check_kernel<T, K>()
+where
+    T: Wide + TryFrom<u8>,
+    K: Kernel<T>,
+{
+    let value = |v: u8| T::try_from(v).ok().unwrap();
+
+    let absent = value(b'z');
+    // The needle of the sweep, `x`, is within the range.
+    let (start, end) = (value(b'w'), value(b'y'));
+
+    sweep("wmemchr", "naive", K::wmemchr, naive::wmemchr);
+    sweep("wmemrchr", "naive", K::wmemrchr, naive::wmemrchr);
+    sweep("wmemcount", "naive", K::wmemcount, naive::wmemcount);
+    sweep(
+        "wmemchr_any",
+        "naive",
+        |needle, haystack| K::wmemchr_any(&[absent, needle], haystack),
+        |needle, haystack| naive::wmemchr_any(&[absent, needle], haystack),
+    );
+    sweep(
+        "wmemchr_range",
+        "naive",
+        |_, haystack| K::wmemchr_range(start, end, haystack),
+        |_, haystack| naive::wmemchr_range(start..=end, haystack),
+    );
+}
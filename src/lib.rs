@@ -18,22 +18,71 @@
 //! ```
 
 #![cfg_attr(not(feature = "std"), no_std)]
-#![cfg_attr(feature = "unstable", feature(stdsimd))]
-#![cfg_attr(feature = "unstable", feature(avx512_target_feature))]
+#![cfg_attr(feature = "unstable", feature(portable_simd))]
 #![deny(missing_docs)]
 
 #[macro_use]
 mod macros;
 
+mod backend;
+mod bytes;
+#[cfg(feature = "capi")]
+pub mod capi;
 mod char;
+#[cfg(feature = "checked")]
+mod checked;
+#[cfg(feature = "std")]
+mod incremental;
+mod kernel;
+mod raw;
+#[cfg(feature = "std")]
+mod stream;
+mod sweep;
+mod vector;
 
+pub mod const_fn;
 pub mod fallback;
 pub mod naive;
+pub mod wchar;
+
+#[cfg(feature = "unstable")]
+pub mod portable;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 #[cfg(all(not(miri), target_arch = "x86_64"))]
 pub mod x86_64;
 
-pub use crate::char::Wide;
+#[cfg(all(not(miri), target_arch = "x86"))]
+#[path = "x86_64/mod.rs"]
+pub mod x86;
+
+// The x86 kernels are shared with x86_64, and refer to their module by its
+// x86_64 name.
+#[cfg(all(not(miri), target_arch = "x86"))]
+use crate::x86 as x86_64;
+
+pub use crate::backend::{backend, force_backend, init, Backend, UnsupportedBackend};
+pub use crate::bytes::{
+    wmemchr32_bytes_be, wmemchr32_bytes_le, wmemchr_bytes_be, wmemchr_bytes_le,
+};
+pub use crate::char::{SwapBytes, Wide, WideRepr};
+#[cfg(feature = "std")]
+pub use crate::incremental::IncrementalSearcher;
+pub use crate::kernel::{check_kernel, register_kernel, unregister_kernel, Kernel};
+pub use crate::raw::{
+    wmemchr_any_nonnull, wmemchr_any_raw, wmemchr_nonnull, wmemchr_range_nonnull,
+    wmemchr_range_raw, wmemchr_raw, wmemchr_swapped_nonnull, wmemchr_swapped_raw, wmemcount_raw,
+    wmemcount_swapped_raw, wmemrchr_nonnull, wmemrchr_raw, wmemrchr_swapped_nonnull,
+    wmemrchr_swapped_raw,
+};
+#[cfg(feature = "std")]
+pub use crate::stream::{Endianness, StreamSearcher};
+
+use core::ops::RangeInclusive;
+use core::slice;
+
+use crate::char::{as_repr_slice, as_repr_slice_mut, to_repr};
 
 /// Returns the index of the first occurrence of a wide character in a slice,
 /// or [`None`] if the character is not found.
@@ -52,14 +101,410 @@ pub use crate::char::Wide;
 /// assert_eq!(wmemchr(wch!(u16, 'z'), haystack), None);
 /// ```
 #[inline]
-pub fn wmemchr<T: Wide>(needle: T, haystack: &[T]) -> Option<usize> {
+pub fn wmemchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let haystack = as_repr_slice(haystack);
+    // SAFETY: A slice is valid for reads of its length.
+    unsafe { wmemchr_ptr(to_repr(needle), haystack.as_ptr(), haystack.len()) }
+}
+
+/// Searches the `len` elements starting at `haystack` for the first occurrence
+/// of a wide character, with the registered kernel if there is one.
+///
+/// # Safety
+///
+/// `haystack` must be non-null, aligned, and valid for reads of `len`
+/// elements.
+#[inline(always)]
+pub(crate) unsafe fn wmemchr_ptr<T: Wide>(
+    needle: T,
+    haystack: *const T,
+    len: usize,
+) -> Option<usize> {
+    let kernel = T::registry().wmemchr();
+
+    let pos = match kernel {
+        Some(kernel) => {
+            let pos = kernel(needle, slice::from_raw_parts(haystack, len));
+            kernel::check_bounds("wmemchr", pos, len)
+        }
+        None => builtin_wmemchr(needle, haystack, len),
+    };
+
+    #[cfg(feature = "checked")]
+    {
+        let haystack = slice::from_raw_parts(haystack, len);
+        checked::check::<T, _, _, _>(
+            "wmemchr",
+            kernel.is_some(),
+            needle,
+            haystack,
+            pos,
+            naive::wmemchr(needle, haystack),
+        );
+    }
+
+    pos
+}
+
+/// Searches with the kernel of the current backend.
+#[inline(always)]
+unsafe fn builtin_wmemchr<T: Wide>(needle: T, haystack: *const T, len: usize) -> Option<usize> {
     cfg_if::cfg_if! {
-        if #[cfg(miri)] {
-            fallback::wmemchr(needle, haystack)
-        } else if #[cfg(target_arch = "x86_64")] {
-            x86_64::wmemchr(needle, haystack)
+        if #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))] {
+            T::wmemchr_x86_64(needle, haystack, len)
+        } else if #[cfg(feature = "unstable")] {
+            match portable::backend() {
+                Backend::Portable => {
+                    T::wmemchr_portable(needle, slice::from_raw_parts(haystack, len))
+                }
+                _ => T::wmemchr_fallback(needle, haystack, len),
+            }
         } else {
-            fallback::wmemchr(needle, haystack)
+            T::wmemchr_fallback(needle, haystack, len)
         }
     }
 }
+
+/// Returns the index of the last occurrence of a wide character in a slice,
+/// or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemrchr;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemrchr(wch!(u16, 'o'), haystack), Some(2));
+/// assert_eq!(wmemrchr(wch!(u16, 'z'), haystack), None);
+/// ```
+#[inline]
+pub fn wmemrchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let haystack = as_repr_slice(haystack);
+    // SAFETY: A slice is valid for reads of its length.
+    unsafe { wmemrchr_ptr(to_repr(needle), haystack.as_ptr(), haystack.len()) }
+}
+
+/// Searches the `len` elements starting at `haystack` for the last occurrence
+/// of a wide character, with the registered kernel if there is one.
+///
+/// # Safety
+///
+/// `haystack` must be non-null, aligned, and valid for reads of `len`
+/// elements.
+#[inline(always)]
+pub(crate) unsafe fn wmemrchr_ptr<T: Wide>(
+    needle: T,
+    haystack: *const T,
+    len: usize,
+) -> Option<usize> {
+    let kernel = T::registry().wmemrchr();
+
+    let pos = match kernel {
+        Some(kernel) => {
+            let pos = kernel(needle, slice::from_raw_parts(haystack, len));
+            kernel::check_bounds("wmemrchr", pos, len)
+        }
+        None => builtin_wmemrchr(needle, haystack, len),
+    };
+
+    #[cfg(feature = "checked")]
+    {
+        let haystack = slice::from_raw_parts(haystack, len);
+        checked::check::<T, _, _, _>(
+            "wmemrchr",
+            kernel.is_some(),
+            needle,
+            haystack,
+            pos,
+            naive::wmemrchr(needle, haystack),
+        );
+    }
+
+    pos
+}
+
+/// Searches for the last occurrence with the kernel of the current backend.
+#[inline(always)]
+unsafe fn builtin_wmemrchr<T: Wide>(needle: T, haystack: *const T, len: usize) -> Option<usize> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))] {
+            T::wmemrchr_x86_64(needle, haystack, len)
+        } else if #[cfg(feature = "unstable")] {
+            match portable::backend() {
+                Backend::Portable => {
+                    T::wmemrchr_portable(needle, slice::from_raw_parts(haystack, len))
+                }
+                _ => T::wmemrchr_fallback(needle, haystack, len),
+            }
+        } else {
+            T::wmemrchr_fallback(needle, haystack, len)
+        }
+    }
+}
+
+/// Returns the number of occurrences of a wide character in a slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemcount;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemcount(wch!(u16, 'o'), haystack), 2);
+/// assert_eq!(wmemcount(wch!(u16, 'z'), haystack), 0);
+/// ```
+#[inline]
+pub fn wmemcount<T: WideRepr>(needle: T, haystack: &[T]) -> usize {
+    let haystack = as_repr_slice(haystack);
+    // SAFETY: A slice is valid for reads of its length.
+    unsafe { wmemcount_ptr(to_repr(needle), haystack.as_ptr(), haystack.len()) }
+}
+
+/// Counts the occurrences of a wide character in the `len` elements starting
+/// at `haystack`, with the registered kernel if there is one.
+///
+/// # Safety
+///
+/// `haystack` must be non-null, aligned, and valid for reads of `len`
+/// elements.
+#[inline(always)]
+pub(crate) unsafe fn wmemcount_ptr<T: Wide>(needle: T, haystack: *const T, len: usize) -> usize {
+    let kernel = T::registry().wmemcount();
+
+    let count = match kernel {
+        Some(kernel) => kernel(needle, slice::from_raw_parts(haystack, len)),
+        None => builtin_wmemcount(needle, haystack, len),
+    };
+
+    #[cfg(feature = "checked")]
+    {
+        let haystack = slice::from_raw_parts(haystack, len);
+        checked::check::<T, _, _, _>(
+            "wmemcount",
+            kernel.is_some(),
+            needle,
+            haystack,
+            count,
+            naive::wmemcount(needle, haystack),
+        );
+    }
+
+    count
+}
+
+/// Counts the occurrences with the kernel of the current backend.
+#[inline(always)]
+unsafe fn builtin_wmemcount<T: Wide>(needle: T, haystack: *const T, len: usize) -> usize {
+    cfg_if::cfg_if! {
+        if #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))] {
+            T::wmemcount_x86_64(needle, haystack, len)
+        } else if #[cfg(feature = "unstable")] {
+            match portable::backend() {
+                Backend::Portable => {
+                    T::wmemcount_portable(needle, slice::from_raw_parts(haystack, len))
+                }
+                _ => T::wmemcount_fallback(needle, haystack, len),
+            }
+        } else {
+            T::wmemcount_fallback(needle, haystack, len)
+        }
+    }
+}
+
+/// Returns the index of the first wide character in a slice that is equal to
+/// any of the needles, or [`None`] if none are found.
+///
+/// On x86_64, searches of 16-bit wide characters for up to 8 needles use the
+/// AVX2 kernel, or the SSE4.2 string instructions if AVX2 is unavailable.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemchr_any;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemchr_any(wch!(u16, "ab"), haystack), Some(4));
+/// assert_eq!(wmemchr_any(wch!(u16, "xyz"), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr_any<T: WideRepr>(needles: &[T], haystack: &[T]) -> Option<usize> {
+    let (needles, haystack) = (as_repr_slice(needles), as_repr_slice(haystack));
+    let kernel = T::Repr::registry().wmemchr_any();
+
+    let pos = match kernel {
+        Some(kernel) => {
+            let pos = kernel(needles, haystack);
+            kernel::check_bounds("wmemchr_any", pos, haystack.len())
+        }
+        None => T::Repr::wmemchr_any(needles, haystack),
+    };
+
+    #[cfg(feature = "checked")]
+    checked::check::<T::Repr, _, _, _>(
+        "wmemchr_any",
+        kernel.is_some(),
+        needles,
+        haystack,
+        pos,
+        naive::wmemchr_any(needles, haystack),
+    );
+
+    pos
+}
+
+/// Returns the index of the first wide character in a slice that is within an
+/// inclusive range, or [`None`] if none are found.
+///
+/// Wide characters are ordered by their [representation], so the range is
+/// signed for signed integer types. On x86_64, searches of 16-bit wide
+/// characters use the AVX2 kernel, or the SSE4.2 string instructions if AVX2
+/// is unavailable.
+///
+/// [representation]: WideRepr::Repr
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemchr_range;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemchr_range(wch!(u16, 'a')..=wch!(u16, 'c'), haystack), Some(4));
+/// assert_eq!(wmemchr_range(wch!(u16, 'x')..=wch!(u16, 'z'), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr_range<T: WideRepr>(range: RangeInclusive<T>, haystack: &[T]) -> Option<usize> {
+    let (start, end) = range.into_inner();
+    let (start, end, haystack) = (to_repr(start), to_repr(end), as_repr_slice(haystack));
+    let kernel = T::Repr::registry().wmemchr_range();
+
+    let pos = match kernel {
+        Some(kernel) => {
+            let pos = kernel(start, end, haystack);
+            kernel::check_bounds("wmemchr_range", pos, haystack.len())
+        }
+        None => T::Repr::wmemchr_range(start, end, haystack),
+    };
+
+    #[cfg(feature = "checked")]
+    checked::check::<T::Repr, _, _, _>(
+        "wmemchr_range",
+        kernel.is_some(),
+        start..=end,
+        haystack,
+        pos,
+        naive::wmemchr_range(start..=end, haystack),
+    );
+
+    pos
+}
+
+/// Returns the index of the first occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// This searches a haystack of opposite endianness in place, such as UTF-16BE
+/// text on a little-endian platform, by swapping the byte order of the needle
+/// rather than the haystack.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00, 0x2000, 0x6200, 0x6100, 0x7200];
+///
+/// assert_eq!(wmemchr_swapped(0x006f, &haystack), Some(1));
+/// assert_eq!(wmemchr_swapped(0x007a, &haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the index of the last occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemrchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00, 0x2000, 0x6200, 0x6100, 0x7200];
+///
+/// assert_eq!(wmemrchr_swapped(0x006f, &haystack), Some(2));
+/// assert_eq!(wmemrchr_swapped(0x007a, &haystack), None);
+/// ```
+#[inline]
+pub fn wmemrchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemrchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the number of occurrences of a byte swapped wide character in a
+/// slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemcount_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00, 0x2000, 0x6200, 0x6100, 0x7200];
+///
+/// assert_eq!(wmemcount_swapped(0x006f, &haystack), 2);
+/// assert_eq!(wmemcount_swapped(0x007a, &haystack), 0);
+/// ```
+#[inline]
+pub fn wmemcount_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> usize
+where
+    T::Repr: SwapBytes,
+{
+    wmemcount(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Reverses the byte order of every wide character in a slice, in place.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::swap_endianness;
+///
+/// let mut data = [0x6600u16, 0x6f00, 0x6f00];
+/// swap_endianness(&mut data);
+///
+/// assert_eq!(data, [0x0066, 0x006f, 0x006f]);
+/// ```
+#[inline]
+pub fn swap_endianness<T: WideRepr>(data: &mut [T])
+where
+    T::Repr: SwapBytes,
+{
+    T::Repr::swap_endianness(as_repr_slice_mut(data))
+}
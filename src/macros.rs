@@ -45,9 +45,26 @@ macro_rules! is_x86_feature_detected {
     };
 }
 /// Test at runtime whether a CPU feature is available on x86/x86_64 platforms.
+///
+/// Without `std`, the features are detected using `cpuid` directly.
 #[cfg(not(feature = "std"))]
 macro_rules! is_x86_feature_detected {
-    ($feature:tt) => {
-        ::core::cfg!(target_feature = $feature)
+    ("sse2") => {
+        $crate::x86_64::cpuid::is_detected($crate::x86_64::cpuid::SSE2)
+    };
+    ("ssse3") => {
+        $crate::x86_64::cpuid::is_detected($crate::x86_64::cpuid::SSSE3)
+    };
+    ("sse4.2") => {
+        $crate::x86_64::cpuid::is_detected($crate::x86_64::cpuid::SSE42)
+    };
+    ("avx2") => {
+        $crate::x86_64::cpuid::is_detected($crate::x86_64::cpuid::AVX2)
+    };
+    ("avx512vl") => {
+        $crate::x86_64::cpuid::is_detected($crate::x86_64::cpuid::AVX512VL)
+    };
+    ("avx512bw") => {
+        $crate::x86_64::cpuid::is_detected($crate::x86_64::cpuid::AVX512BW)
     };
 }
@@ -1,6 +1,8 @@
 //! A naive implementation.
 
-use crate::char::{KernelFn, Wide};
+use core::ops::RangeInclusive;
+
+use crate::char::{as_repr_slice, to_repr, SwapBytes, Wide, WideRepr};
 
 /// Returns the index of the first occurrence of a wide character in a slice,
 /// or [`None`] if the character is not found.
@@ -19,14 +21,176 @@ use crate::char::{KernelFn, Wide};
 /// assert_eq!(wmemchr(wch!(u16, 'z'), haystack), None);
 /// ```
 #[inline(always)]
-pub fn wmemchr<T: Wide>(needle: T, haystack: &[T]) -> Option<usize> {
-    T::wmemchr_naive(needle, haystack)
+pub fn wmemchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    T::Repr::wmemchr_naive(to_repr(needle), as_repr_slice(haystack))
 }
 
-pub(crate) struct Kernel;
+/// Returns the index of the last occurrence of a wide character in a slice,
+/// or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::naive::wmemrchr;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemrchr(wch!(u16, 'o'), haystack), Some(2));
+/// assert_eq!(wmemrchr(wch!(u16, 'z'), haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemrchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    T::Repr::wmemrchr_naive(to_repr(needle), as_repr_slice(haystack))
+}
 
-impl<T: Copy + Eq> KernelFn<T> for Kernel {
-    fn kernel(needle: T, haystack: &[T]) -> Option<usize> {
-        haystack.iter().position(|&c| c == needle)
-    }
+/// Returns the number of occurrences of a wide character in a slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::naive::wmemcount;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemcount(wch!(u16, 'o'), haystack), 2);
+/// assert_eq!(wmemcount(wch!(u16, 'z'), haystack), 0);
+/// ```
+#[inline]
+pub fn wmemcount<T: WideRepr>(needle: T, haystack: &[T]) -> usize {
+    let needle = to_repr(needle);
+    as_repr_slice(haystack)
+        .iter()
+        .filter(|&&c| c == needle)
+        .count()
+}
+
+/// Returns the index of the first occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// This searches a haystack of opposite endianness in place, by swapping the
+/// byte order of the needle.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::naive::wmemchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemchr_swapped(0x006f, &haystack), Some(1));
+/// assert_eq!(wmemchr_swapped(0x007a, &haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the index of the last occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::naive::wmemrchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemrchr_swapped(0x006f, &haystack), Some(2));
+/// assert_eq!(wmemrchr_swapped(0x007a, &haystack), None);
+/// ```
+#[inline]
+pub fn wmemrchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemrchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the number of occurrences of a byte swapped wide character in a
+/// slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::naive::wmemcount_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemcount_swapped(0x006f, &haystack), 2);
+/// assert_eq!(wmemcount_swapped(0x007a, &haystack), 0);
+/// ```
+#[inline]
+pub fn wmemcount_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> usize
+where
+    T::Repr: SwapBytes,
+{
+    wmemcount(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the index of the first wide character in a slice that is equal to
+/// any of the needles, or [`None`] if none are found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::naive::wmemchr_any;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemchr_any(wch!(u16, "ab"), haystack), Some(4));
+/// assert_eq!(wmemchr_any(wch!(u16, "xyz"), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr_any<T: WideRepr>(needles: &[T], haystack: &[T]) -> Option<usize> {
+    let needles = as_repr_slice(needles);
+    as_repr_slice(haystack)
+        .iter()
+        .position(|c| needles.contains(c))
+}
+
+/// Returns the index of the first wide character in a slice that is within an
+/// inclusive range, or [`None`] if none are found.
+///
+/// Wide characters are ordered by their [representation].
+///
+/// [representation]: WideRepr::Repr
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::naive::wmemchr_range;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemchr_range(wch!(u16, 'a')..=wch!(u16, 'c'), haystack), Some(4));
+/// assert_eq!(wmemchr_range(wch!(u16, 'x')..=wch!(u16, 'z'), haystack), None);
+/// ```
+#[inline]
+pub fn wmemchr_range<T: WideRepr>(range: RangeInclusive<T>, haystack: &[T]) -> Option<usize> {
+    let (start, end) = range.into_inner();
+    let (start, end) = (to_repr(start), to_repr(end));
+    as_repr_slice(haystack)
+        .iter()
+        .position(|&c| start <= c && c <= end)
 }
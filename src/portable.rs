@@ -0,0 +1,407 @@
+//! Portable SIMD implementation using [`core::simd`].
+//!
+//! This is only available with the `unstable` feature. On targets other than
+//! x86_64, and under Miri, the search functions use it in place of the
+//! [`fallback`] implementation.
+//!
+//! [`fallback`]: crate::fallback
+
+use core::mem;
+use core::simd::cmp::SimdPartialEq;
+use core::simd::{Mask, Simd, SimdElement};
+use core::slice;
+
+#[cfg(any(miri, not(any(target_arch = "x86", target_arch = "x86_64"))))]
+use core::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(any(miri, not(any(target_arch = "x86", target_arch = "x86_64"))))]
+use crate::backend::Backend;
+use crate::char::{as_repr_slice, to_repr, Wide, WideRepr};
+
+/// Returns the index of the first occurrence of a wide character in a slice,
+/// or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::portable::wmemchr;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemchr(wch!(u16, 'o'), haystack), Some(1));
+/// assert_eq!(wmemchr(wch!(u16, 'z'), haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    let pos = T::Repr::wmemchr_portable(needle, haystack);
+
+    #[cfg(feature = "checked")]
+    crate::checked::check_backend(
+        concat!(module_path!(), "::wmemchr"),
+        crate::backend::Backend::Portable,
+        needle,
+        haystack,
+        pos,
+        crate::naive::wmemchr(needle, haystack),
+    );
+
+    pos
+}
+
+/// Returns the index of the last occurrence of a wide character in a slice,
+/// or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::portable::wmemrchr;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemrchr(wch!(u16, 'o'), haystack), Some(2));
+/// assert_eq!(wmemrchr(wch!(u16, 'z'), haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemrchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    let pos = T::Repr::wmemrchr_portable(needle, haystack);
+
+    #[cfg(feature = "checked")]
+    crate::checked::check_backend(
+        concat!(module_path!(), "::wmemrchr"),
+        crate::backend::Backend::Portable,
+        needle,
+        haystack,
+        pos,
+        crate::naive::wmemrchr(needle, haystack),
+    );
+
+    pos
+}
+
+/// Returns the number of occurrences of a wide character in a slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::portable::wmemcount;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemcount(wch!(u16, 'o'), haystack), 2);
+/// assert_eq!(wmemcount(wch!(u16, 'z'), haystack), 0);
+/// ```
+#[inline(always)]
+pub fn wmemcount<T: WideRepr>(needle: T, haystack: &[T]) -> usize {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    let count = T::Repr::wmemcount_portable(needle, haystack);
+
+    #[cfg(feature = "checked")]
+    crate::checked::check_backend(
+        concat!(module_path!(), "::wmemcount"),
+        crate::backend::Backend::Portable,
+        needle,
+        haystack,
+        count,
+        crate::naive::wmemcount(needle, haystack),
+    );
+
+    count
+}
+
+/// Returns the index of the first wide character in a slice that is equal to
+/// any of the needles, or [`None`] if none are found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::portable::wmemchr_any;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemchr_any(wch!(u16, "ab"), haystack), Some(4));
+/// assert_eq!(wmemchr_any(wch!(u16, "xyz"), haystack), None);
+/// ```
+#[inline(always)]
+pub fn wmemchr_any<T: WideRepr>(needles: &[T], haystack: &[T]) -> Option<usize> {
+    let (needles, haystack) = (as_repr_slice(needles), as_repr_slice(haystack));
+    let pos = T::Repr::wmemchr_any_portable(needles, haystack);
+
+    #[cfg(feature = "checked")]
+    crate::checked::check_backend(
+        concat!(module_path!(), "::wmemchr_any"),
+        crate::backend::Backend::Portable,
+        needles,
+        haystack,
+        pos,
+        crate::naive::wmemchr_any(needles, haystack),
+    );
+
+    pos
+}
+
+/// Sentinel for a backend that has not yet been detected.
+#[cfg(any(miri, not(any(target_arch = "x86", target_arch = "x86_64"))))]
+const UNINIT: u8 = u8::MAX;
+
+/// The backend used by the search functions, either this implementation or
+/// the [`fallback`] implementation.
+///
+/// [`fallback`]: crate::fallback
+#[cfg(any(miri, not(any(target_arch = "x86", target_arch = "x86_64"))))]
+static BACKEND: AtomicU8 = AtomicU8::new(UNINIT);
+
+/// Returns the backend requested by the environment, or otherwise this
+/// implementation.
+#[cfg(any(miri, not(any(target_arch = "x86", target_arch = "x86_64"))))]
+fn detect() -> Backend {
+    #[cfg(feature = "std")]
+    {
+        if let Some(backend) = Backend::from_env() {
+            return backend;
+        }
+    }
+
+    Backend::Portable
+}
+
+/// Returns the backend used by the search functions, detecting it if needed.
+#[cfg(any(miri, not(any(target_arch = "x86", target_arch = "x86_64"))))]
+#[inline(always)]
+pub(crate) fn backend() -> Backend {
+    let backend = BACKEND.load(Ordering::Relaxed);
+    if backend != UNINIT {
+        // Only valid backends are ever stored, other than `UNINIT`.
+        return Backend::from_u8(backend).unwrap();
+    }
+
+    let detected = detect();
+
+    // Do not overwrite a backend forced by another thread in the meantime.
+    match BACKEND.compare_exchange(UNINIT, detected as u8, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => detected,
+        Err(backend) => Backend::from_u8(backend).unwrap(),
+    }
+}
+
+/// Sets the backend used by the search functions.
+///
+/// The backend must be supported.
+#[cfg(any(miri, not(any(target_arch = "x86", target_arch = "x86_64"))))]
+pub(crate) fn set_backend(backend: Backend) {
+    debug_assert!(backend.is_supported());
+
+    BACKEND.store(backend as u8, Ordering::Relaxed);
+}
+
+/// The searches of a wide character type, which are performed on vectors of
+/// its representation as a [`SimdElement`].
+pub(crate) trait Search: Sized {
+    fn forward(needle: Self, haystack: &[Self]) -> Option<usize>;
+    fn reverse(needle: Self, haystack: &[Self]) -> Option<usize>;
+    fn count(needle: Self, haystack: &[Self]) -> usize;
+    fn any(needles: &[Self], haystack: &[Self]) -> Option<usize>;
+}
+
+macro_rules! impl_search {
+    ($($ty:ty => $elem:ty, $lanes:literal;)*) => {
+        $(
+            impl Search for $ty {
+                #[inline(always)]
+                fn forward(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                    let v_needle = Simd::<$elem, $lanes>::splat(needle as $elem);
+                    forward_by(elements(haystack), |chunk| eq(chunk, v_needle))
+                }
+
+                #[inline(always)]
+                fn reverse(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                    let v_needle = Simd::<$elem, $lanes>::splat(needle as $elem);
+                    reverse_by(elements(haystack), |chunk| eq(chunk, v_needle))
+                }
+
+                #[inline(always)]
+                fn count(needle: $ty, haystack: &[$ty]) -> usize {
+                    let v_needle = Simd::<$elem, $lanes>::splat(needle as $elem);
+                    count_by(elements(haystack), |chunk| eq(chunk, v_needle))
+                }
+
+                #[inline(always)]
+                fn any(needles: &[$ty], haystack: &[$ty]) -> Option<usize> {
+                    let needles: &[$elem] = elements(needles);
+                    forward_by(elements(haystack), |chunk: Simd<$elem, $lanes>| {
+                        needles
+                            .iter()
+                            .fold(0, |mask, &needle| mask | eq(chunk, Simd::splat(needle)))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+// Each type is searched in 32 byte vectors.
+impl_search! {
+    u8 => u8, 32;
+    u16 => u16, 16;
+    u32 => u32, 8;
+    i8 => i8, 32;
+    i16 => i16, 16;
+    i32 => i32, 8;
+    // A `char` is represented in memory as its `u32` scalar value.
+    char => u32, 8;
+}
+
+/// Reinterprets a slice of wide characters as a slice of their representation
+/// as a [`SimdElement`].
+#[inline(always)]
+fn elements<T: Wide, E: SimdElement>(slice: &[T]) -> &[E] {
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<E>());
+    debug_assert_eq!(mem::align_of::<T>(), mem::align_of::<E>());
+
+    // SAFETY: Each wide character type is only reinterpreted as an integer of
+    //         the same size, for which every bit pattern is valid.
+    unsafe { slice::from_raw_parts(slice.as_ptr() as *const E, slice.len()) }
+}
+
+/// Returns a bitmask of the lanes of `a` that are equal to those of `b`.
+#[inline(always)]
+fn eq<E: SimdElement, const N: usize>(a: Simd<E, N>, b: Simd<E, N>) -> u64
+where
+    Simd<E, N>: SimdPartialEq<Mask = Mask<E::Mask, N>>,
+{
+    a.simd_eq(b).to_bitmask()
+}
+
+/// Returns a bitmask of the first `len` lanes of a vector.
+#[inline(always)]
+fn lanes(len: usize) -> u64 {
+    debug_assert!(len < 64);
+
+    (1 << len) - 1
+}
+
+/// Searches for the first element of a haystack for which `eq` sets the bits of
+/// the bitmask.
+#[inline(always)]
+fn forward_by<E, F, const N: usize>(haystack: &[E], eq: F) -> Option<usize>
+where
+    E: SimdElement + Default,
+    F: Fn(Simd<E, N>) -> u64,
+{
+    let len = haystack.len();
+
+    // If haystack length is less than number of elements in a vector, then load
+    // it into a vector and discard the lanes past its end.
+    if len < N {
+        let mask = eq(Simd::load_or_default(haystack)) & lanes(len);
+        return forward_pos(mask);
+    }
+
+    let mut chunks = haystack.chunks_exact(N);
+    for (i, chunk) in (&mut chunks).enumerate() {
+        if let Some(pos) = forward_pos(eq(Simd::from_slice(chunk))) {
+            return Some(i * N + pos);
+        }
+    }
+
+    // We can search the remaining elements by loading the last vector of the
+    // haystack, overlapping elements that have already been searched.
+    if !chunks.remainder().is_empty() {
+        let offset = len - N;
+        if let Some(pos) = forward_pos(eq(Simd::from_slice(&haystack[offset..]))) {
+            return Some(offset + pos);
+        }
+    }
+
+    None
+}
+
+/// Searches for the last element of a haystack for which `eq` sets the bits of
+/// the bitmask.
+#[inline(always)]
+fn reverse_by<E, F, const N: usize>(haystack: &[E], eq: F) -> Option<usize>
+where
+    E: SimdElement + Default,
+    F: Fn(Simd<E, N>) -> u64,
+{
+    let len = haystack.len();
+
+    // If haystack length is less than number of elements in a vector, then load
+    // it into a vector and discard the lanes past its end.
+    if len < N {
+        let mask = eq(Simd::load_or_default(haystack)) & lanes(len);
+        return reverse_pos(mask);
+    }
+
+    let mut chunks = haystack.rchunks_exact(N);
+    for (i, chunk) in (&mut chunks).enumerate() {
+        if let Some(pos) = reverse_pos(eq(Simd::from_slice(chunk))) {
+            return Some(len - (i + 1) * N + pos);
+        }
+    }
+
+    // We can search the remaining elements by loading the first vector of the
+    // haystack, overlapping elements that have already been searched.
+    if !chunks.remainder().is_empty() {
+        return reverse_pos(eq(Simd::from_slice(&haystack[..N])));
+    }
+
+    None
+}
+
+/// Counts the elements of a haystack for which `eq` sets the bits of the
+/// bitmask.
+#[inline(always)]
+fn count_by<E, F, const N: usize>(haystack: &[E], eq: F) -> usize
+where
+    E: SimdElement + Default,
+    F: Fn(Simd<E, N>) -> u64,
+{
+    let mut chunks = haystack.chunks_exact(N);
+    let mut count = 0;
+
+    for chunk in &mut chunks {
+        count += eq(Simd::from_slice(chunk)).count_ones() as usize;
+    }
+
+    // Elements must not be counted twice, so the remaining elements are loaded
+    // into a vector, discarding the lanes past the end of the haystack.
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        let mask = eq(Simd::load_or_default(remainder)) & lanes(remainder.len());
+        count += mask.count_ones() as usize;
+    }
+
+    count
+}
+
+#[inline(always)]
+fn forward_pos(mask: u64) -> Option<usize> {
+    if mask != 0 {
+        Some(mask.trailing_zeros() as usize)
+    } else {
+        None
+    }
+}
+
+#[inline(always)]
+fn reverse_pos(mask: u64) -> Option<usize> {
+    if mask != 0 {
+        Some(63 - mask.leading_zeros() as usize)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,681 @@
+//! Search functions over raw pointers, for callers across an FFI boundary that
+//! have a pointer and a length rather than a slice.
+
+use core::ops::RangeInclusive;
+use core::ptr::{self, NonNull};
+use core::slice;
+
+use crate::char::{to_repr, SwapBytes, WideRepr};
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+use crate::x86_64;
+
+/// Returns a pointer to the first occurrence of a wide character in the `len`
+/// elements starting at `ptr`, or a null pointer if the character is not found,
+/// like libc `wmemchr`.
+///
+/// # Safety
+///
+/// Unless `len` is zero, `ptr` must be non-null, aligned, and valid for reads
+/// of `len` elements of `T`, which must not be mutated for the duration of the
+/// call. If `len` is zero, then `ptr` may be null or dangling.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wchar::wch;
+/// use wmemchr::wmemchr_raw;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemchr_raw(wch!(u16, 'o'), ptr, len), ptr.add(1));
+///     assert_eq!(wmemchr_raw(wch!(u16, 'z'), ptr, len), ptr::null());
+///     assert_eq!(wmemchr_raw(wch!(u16, 'z'), ptr::null(), 0), ptr::null());
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_raw<T: WideRepr>(needle: T, ptr: *const T, len: usize) -> *const T {
+    or_null(wmemchr_nonnull(needle, ptr, len))
+}
+
+/// Returns a pointer to the first occurrence of a wide character in the `len`
+/// elements starting at `ptr`, or [`None`] if the character is not found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemchr_nonnull;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     let found = wmemchr_nonnull(wch!(u16, 'o'), ptr, len).unwrap();
+///     assert_eq!(found.as_ptr() as *const u16, ptr.add(1));
+///     assert_eq!(wmemchr_nonnull(wch!(u16, 'z'), ptr, len), None);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_nonnull<T: WideRepr>(
+    needle: T,
+    ptr: *const T,
+    len: usize,
+) -> Option<NonNull<T>> {
+    search(ptr, len, |ptr, len| {
+        crate::wmemchr_ptr(to_repr(needle), ptr, len)
+    })
+}
+
+/// Returns a pointer to the last occurrence of a wide character in the `len`
+/// elements starting at `ptr`, or a null pointer if the character is not found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wchar::wch;
+/// use wmemchr::wmemrchr_raw;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemrchr_raw(wch!(u16, 'o'), ptr, len), ptr.add(2));
+///     assert_eq!(wmemrchr_raw(wch!(u16, 'z'), ptr, len), ptr::null());
+///     assert_eq!(wmemrchr_raw(wch!(u16, 'z'), ptr::null(), 0), ptr::null());
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemrchr_raw<T: WideRepr>(needle: T, ptr: *const T, len: usize) -> *const T {
+    or_null(wmemrchr_nonnull(needle, ptr, len))
+}
+
+/// Returns a pointer to the last occurrence of a wide character in the `len`
+/// elements starting at `ptr`, or [`None`] if the character is not found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemrchr_nonnull;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     let found = wmemrchr_nonnull(wch!(u16, 'o'), ptr, len).unwrap();
+///     assert_eq!(found.as_ptr() as *const u16, ptr.add(2));
+///     assert_eq!(wmemrchr_nonnull(wch!(u16, 'z'), ptr, len), None);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemrchr_nonnull<T: WideRepr>(
+    needle: T,
+    ptr: *const T,
+    len: usize,
+) -> Option<NonNull<T>> {
+    search(ptr, len, |ptr, len| {
+        crate::wmemrchr_ptr(to_repr(needle), ptr, len)
+    })
+}
+
+/// Returns the number of occurrences of a wide character in the `len`
+/// elements starting at `ptr`.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wchar::wch;
+/// use wmemchr::wmemcount_raw;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemcount_raw(wch!(u16, 'o'), ptr, len), 2);
+///     assert_eq!(wmemcount_raw(wch!(u16, 'z'), ptr, len), 0);
+///     assert_eq!(wmemcount_raw(wch!(u16, 'z'), ptr::null(), 0), 0);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemcount_raw<T: WideRepr>(needle: T, ptr: *const T, len: usize) -> usize {
+    // An empty haystack is not searched, since `ptr` may be null.
+    if len == 0 {
+        return 0;
+    }
+
+    debug_assert!(!ptr.is_null());
+
+    crate::wmemcount_ptr(to_repr(needle), ptr as *const T::Repr, len)
+}
+
+/// Returns a pointer to the first wide character in the `len` elements
+/// starting at `ptr` that is equal to any of the needles, or a null pointer if
+/// none are found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wchar::wch;
+/// use wmemchr::wmemchr_any_raw;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemchr_any_raw(wch!(u16, "ab"), ptr, len), ptr.add(4));
+///     assert_eq!(wmemchr_any_raw(wch!(u16, "xyz"), ptr, len), ptr::null());
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_any_raw<T: WideRepr>(needles: &[T], ptr: *const T, len: usize) -> *const T {
+    or_null(wmemchr_any_nonnull(needles, ptr, len))
+}
+
+/// Returns a pointer to the first wide character in the `len` elements
+/// starting at `ptr` that is equal to any of the needles, or [`None`] if none
+/// are found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemchr_any_nonnull;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     let found = wmemchr_any_nonnull(wch!(u16, "ab"), ptr, len).unwrap();
+///     assert_eq!(found.as_ptr() as *const u16, ptr.add(4));
+///     assert_eq!(wmemchr_any_nonnull(wch!(u16, "xyz"), ptr, len), None);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_any_nonnull<T: WideRepr>(
+    needles: &[T],
+    ptr: *const T,
+    len: usize,
+) -> Option<NonNull<T>> {
+    search(ptr, len, |ptr, len| {
+        crate::wmemchr_any(needles, slice::from_raw_parts(ptr as *const T, len))
+    })
+}
+
+/// Returns a pointer to the first wide character in the `len` elements
+/// starting at `ptr` that is within an inclusive range, or a null pointer if
+/// none are found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wchar::wch;
+/// use wmemchr::wmemchr_range_raw;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemchr_range_raw(wch!(u16, 'a')..=wch!(u16, 'c'), ptr, len), ptr.add(4));
+///     assert_eq!(wmemchr_range_raw(wch!(u16, 'x')..=wch!(u16, 'z'), ptr, len), ptr::null());
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_range_raw<T: WideRepr>(
+    range: RangeInclusive<T>,
+    ptr: *const T,
+    len: usize,
+) -> *const T {
+    or_null(wmemchr_range_nonnull(range, ptr, len))
+}
+
+/// Returns a pointer to the first wide character in the `len` elements
+/// starting at `ptr` that is within an inclusive range, or [`None`] if none
+/// are found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wchar::wch;
+/// use wmemchr::wmemchr_range_nonnull;
+///
+/// let haystack = wch!(u16, "foo bar");
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     let found = wmemchr_range_nonnull(wch!(u16, 'a')..=wch!(u16, 'c'), ptr, len).unwrap();
+///     assert_eq!(found.as_ptr() as *const u16, ptr.add(4));
+///     assert_eq!(wmemchr_range_nonnull(wch!(u16, 'x')..=wch!(u16, 'z'), ptr, len), None);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_range_nonnull<T: WideRepr>(
+    range: RangeInclusive<T>,
+    ptr: *const T,
+    len: usize,
+) -> Option<NonNull<T>> {
+    search(ptr, len, |ptr, len| {
+        crate::wmemchr_range(range, slice::from_raw_parts(ptr as *const T, len))
+    })
+}
+
+/// Returns a pointer to the first occurrence of a byte swapped wide character
+/// in the `len` elements starting at `ptr`, or a null pointer if the character
+/// is not found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wmemchr::wmemchr_swapped_raw;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemchr_swapped_raw(0x006f, ptr, len), ptr.add(1));
+///     assert_eq!(wmemchr_swapped_raw(0x007a, ptr, len), ptr::null());
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_swapped_raw<T: WideRepr>(needle: T, ptr: *const T, len: usize) -> *const T
+where
+    T::Repr: SwapBytes,
+{
+    or_null(wmemchr_swapped_nonnull(needle, ptr, len))
+}
+
+/// Returns a pointer to the first occurrence of a byte swapped wide character
+/// in the `len` elements starting at `ptr`, or [`None`] if the character is not
+/// found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemchr_swapped_nonnull;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     let found = wmemchr_swapped_nonnull(0x006f, ptr, len).unwrap();
+///     assert_eq!(found.as_ptr() as *const u16, ptr.add(1));
+///     assert_eq!(wmemchr_swapped_nonnull(0x007a, ptr, len), None);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemchr_swapped_nonnull<T: WideRepr>(
+    needle: T,
+    ptr: *const T,
+    len: usize,
+) -> Option<NonNull<T>>
+where
+    T::Repr: SwapBytes,
+{
+    search(ptr, len, |ptr, len| {
+        crate::wmemchr_ptr(to_repr(needle).swap_bytes(), ptr, len)
+    })
+}
+
+/// Returns a pointer to the last occurrence of a byte swapped wide character
+/// in the `len` elements starting at `ptr`, or a null pointer if the character
+/// is not found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wmemchr::wmemrchr_swapped_raw;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemrchr_swapped_raw(0x006f, ptr, len), ptr.add(2));
+///     assert_eq!(wmemrchr_swapped_raw(0x007a, ptr, len), ptr::null());
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemrchr_swapped_raw<T: WideRepr>(needle: T, ptr: *const T, len: usize) -> *const T
+where
+    T::Repr: SwapBytes,
+{
+    or_null(wmemrchr_swapped_nonnull(needle, ptr, len))
+}
+
+/// Returns a pointer to the last occurrence of a byte swapped wide character
+/// in the `len` elements starting at `ptr`, or [`None`] if the character is not
+/// found.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::wmemrchr_swapped_nonnull;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     let found = wmemrchr_swapped_nonnull(0x006f, ptr, len).unwrap();
+///     assert_eq!(found.as_ptr() as *const u16, ptr.add(2));
+///     assert_eq!(wmemrchr_swapped_nonnull(0x007a, ptr, len), None);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemrchr_swapped_nonnull<T: WideRepr>(
+    needle: T,
+    ptr: *const T,
+    len: usize,
+) -> Option<NonNull<T>>
+where
+    T::Repr: SwapBytes,
+{
+    search(ptr, len, |ptr, len| {
+        crate::wmemrchr_ptr(to_repr(needle).swap_bytes(), ptr, len)
+    })
+}
+
+/// Returns the number of occurrences of a byte swapped wide character in the
+/// `len` elements starting at `ptr`.
+///
+/// # Safety
+///
+/// See [`wmemchr_raw`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::ptr;
+///
+/// use wmemchr::wmemcount_swapped_raw;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+/// let (ptr, len) = (haystack.as_ptr(), haystack.len());
+///
+/// unsafe {
+///     assert_eq!(wmemcount_swapped_raw(0x006f, ptr, len), 2);
+///     assert_eq!(wmemcount_swapped_raw(0x007a, ptr, len), 0);
+///     assert_eq!(wmemcount_swapped_raw(0x006f, ptr::null(), 0), 0);
+/// }
+/// ```
+#[inline]
+pub unsafe fn wmemcount_swapped_raw<T: WideRepr>(needle: T, ptr: *const T, len: usize) -> usize
+where
+    T::Repr: SwapBytes,
+{
+    // An empty haystack is not searched, since `ptr` may be null.
+    if len == 0 {
+        return 0;
+    }
+
+    debug_assert!(!ptr.is_null());
+
+    crate::wmemcount_ptr(to_repr(needle).swap_bytes(), ptr as *const T::Repr, len)
+}
+
+// Only the width of `wchar_t` is used by the `wchar` module, and the other
+// width only by the `capi` module.
+macro_rules! wcs {
+    ($($ty:ty: $wcslen:ident, $wcsnlen:ident, $wcschr:ident;)*) => {
+        $(
+            /// Returns the number of wide characters before the null
+            /// terminator of the string `s`.
+            ///
+            /// # Safety
+            ///
+            /// `s` must be non-null, aligned, and valid for reads up to and
+            /// including its null terminator.
+            #[inline]
+            #[cfg_attr(not(feature = "capi"), allow(dead_code))]
+            pub(crate) unsafe fn $wcslen(s: *const $ty) -> usize {
+                $wcsnlen(s, usize::MAX)
+            }
+
+            /// Returns the number of wide characters before the null
+            /// terminator of the string `s`, or `maxlen` if none of the first
+            /// `maxlen` wide characters is a null terminator.
+            ///
+            /// # Safety
+            ///
+            /// Unless `maxlen` is zero, `s` must be non-null, aligned, and
+            /// valid for reads up to and including its null terminator or its
+            /// `maxlen`th wide character, whichever comes first.
+            #[inline]
+            #[cfg_attr(not(feature = "capi"), allow(dead_code))]
+            pub(crate) unsafe fn $wcsnlen(s: *const $ty, maxlen: usize) -> usize {
+                let len = {
+                    cfg_if::cfg_if! {
+                        if #[cfg(miri)] {
+                            // The kernels read outside of the string, within
+                            // the vectors containing it, which Miri rejects.
+                            // So it is read one wide character at a time.
+                            wcsnlen_naive(s, maxlen)
+                        } else if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
+                            x86_64::$wcsnlen(s, maxlen)
+                        } else {
+                            crate::fallback::wcsnlen(s, maxlen)
+                        }
+                    }
+                };
+
+                #[cfg(feature = "checked")]
+                {
+                    // If `maxlen` is zero, then `s` may be null.
+                    if maxlen != 0 {
+                        let expected = wcsnlen_naive(s, maxlen);
+                        crate::checked::check::<$ty, _, _, _>(
+                            stringify!($wcsnlen),
+                            false,
+                            0 as $ty,
+                            slice::from_raw_parts(s, expected),
+                            len,
+                            expected,
+                        );
+                    }
+                }
+
+                len
+            }
+
+            /// Returns a pointer to the first occurrence of `c` in the string
+            /// `s`, or a null pointer if it is not found, searching the string
+            /// once.
+            ///
+            /// The null terminator is part of the string, so searching for a
+            /// null wide character returns a pointer to the terminator.
+            ///
+            /// # Safety
+            ///
+            /// `s` must be non-null, aligned, and valid for reads up to and
+            /// including its null terminator.
+            #[inline]
+            #[allow(dead_code)]
+            pub(crate) unsafe fn $wcschr(s: *const $ty, c: $ty) -> *const $ty {
+                let pos = {
+                    cfg_if::cfg_if! {
+                        if #[cfg(miri)] {
+                            // As for the length, the string is read one wide
+                            // character at a time with Miri.
+                            wcschr_naive(s, c)
+                        } else if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
+                            x86_64::$wcschr(s, c)
+                        } else {
+                            crate::fallback::wcschr(s, c)
+                        }
+                    }
+                };
+
+                #[cfg(feature = "checked")]
+                {
+                    let expected = wcschr_naive(s, c);
+                    crate::checked::check::<$ty, _, _, _>(
+                        stringify!($wcschr),
+                        false,
+                        c,
+                        slice::from_raw_parts(s, expected + 1),
+                        pos,
+                        expected,
+                    );
+                }
+
+                // The search stops at the null terminator if `c` is not found.
+                let found = s.add(pos);
+                if *found == c {
+                    found
+                } else {
+                    ptr::null()
+                }
+            }
+        )*
+    };
+}
+
+wcs! {
+    u16: wcslen16, wcsnlen16, wcschr16;
+    u32: wcslen32, wcsnlen32, wcschr32;
+}
+
+/// Returns the number of wide characters before the null terminator of the
+/// string `s`, or `maxlen`, reading one wide character at a time.
+#[cfg(any(miri, feature = "checked"))]
+#[inline(always)]
+unsafe fn wcsnlen_naive<T: Copy + PartialEq + From<u8>>(s: *const T, maxlen: usize) -> usize {
+    let mut len = 0;
+    while len < maxlen && *s.add(len) != T::from(0) {
+        len += 1;
+    }
+    len
+}
+
+/// Returns the index of the first occurrence of `c` in the string `s`, or of
+/// its null terminator, reading one wide character at a time.
+#[cfg(any(miri, feature = "checked"))]
+#[inline(always)]
+unsafe fn wcschr_naive<T: Copy + PartialEq + From<u8>>(s: *const T, c: T) -> usize {
+    let mut len = 0;
+    while *s.add(len) != c && *s.add(len) != T::from(0) {
+        len += 1;
+    }
+    len
+}
+
+/// Returns the pointer found, or a null pointer if none is found.
+#[inline(always)]
+fn or_null<T>(found: Option<NonNull<T>>) -> *const T {
+    match found {
+        Some(found) => found.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Runs a search of the `len` elements starting at `ptr`, as their
+/// representation, and returns a pointer to the element found.
+///
+/// An empty haystack is not searched, since `ptr` may be null.
+#[inline(always)]
+unsafe fn search<T, F>(ptr: *const T, len: usize, f: F) -> Option<NonNull<T>>
+where
+    T: WideRepr,
+    F: FnOnce(*const T::Repr, usize) -> Option<usize>,
+{
+    if len == 0 {
+        return None;
+    }
+
+    debug_assert!(!ptr.is_null());
+
+    // SAFETY: By the caller contract, `ptr` is valid for reads of `len`
+    //         elements, and the index found is within them, as the indices
+    //         returned by registered kernels are checked.
+    let pos = f(ptr as *const T::Repr, len)?;
+    Some(NonNull::new_unchecked(ptr.add(pos) as *mut T))
+}
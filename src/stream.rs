@@ -0,0 +1,236 @@
+//! Searches of wide character streams, which need not fit in memory.
+
+use std::io::{self, Read};
+use std::{mem, ptr, slice};
+
+use crate::char::{as_repr_slice, SwapBytes, WideRepr};
+
+/// The byte order of the wide characters in a stream.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Endianness {
+    /// Little-endian, such as UTF-16LE.
+    Little,
+    /// Big-endian, such as UTF-16BE.
+    Big,
+}
+
+impl Endianness {
+    /// The byte order of the target platform.
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Endianness = Endianness::Little;
+    /// The byte order of the target platform.
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Endianness = Endianness::Big;
+}
+
+/// The size of the chunks read from a stream, in bytes.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// An iterator over the offsets of a needle in a stream of wide characters,
+/// read from an [`io::Read`] source in large chunks.
+///
+/// The stream is a sequence of code units represented by `T`, in either byte
+/// order, such as UTF-16 or UTF-32 text. The offsets yielded are absolute, counted in
+/// code units from the start of the stream, and a code unit split across
+/// reads is reassembled. A trailing byte that does not form a whole code unit
+/// is ignored.
+///
+/// A needle may be a single code unit, or a substring of code units, which is
+/// found even if it spans the boundary between two chunks. Every occurrence is
+/// found, including overlapping occurrences of a substring.
+///
+/// A [`BufRead`](std::io::BufRead) source is read through its [`io::Read`]
+/// implementation, so there is no need to wrap a source in a
+/// [`BufReader`](std::io::BufReader).
+///
+/// # Errors
+///
+/// An error reading from the source is yielded, and the search is resumed on
+/// the next call to [`next`](Iterator::next). Reads that are interrupted are
+/// retried.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::{Endianness, StreamSearcher};
+///
+/// let utf16be = b"\0f\0o\0o\0 \0b\0a\0r\0 \0f\0o\0o";
+///
+/// let units = StreamSearcher::new(&utf16be[..], u16::from(b'o'), Endianness::Big);
+/// let offsets: Vec<u64> = units.collect::<Result<_, _>>()?;
+/// assert_eq!(offsets, [1, 2, 9, 10]);
+///
+/// let foo = [u16::from(b'f'), u16::from(b'o'), u16::from(b'o')];
+/// let substrings = StreamSearcher::substring(&utf16be[..], &foo, Endianness::Big);
+/// let offsets: Vec<u64> = substrings.collect::<Result<_, _>>()?;
+/// assert_eq!(offsets, [0, 8]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct StreamSearcher<R, T: WideRepr> {
+    reader: R,
+    /// The needle, in the byte order of the stream.
+    needle: Box<[T::Repr]>,
+    /// The chunk of the stream being searched.
+    buf: Box<[T::Repr]>,
+    /// The number of whole code units in `buf`.
+    len: usize,
+    /// The number of bytes of a partial code unit following them.
+    partial: usize,
+    /// The position in `buf` from which to continue searching.
+    pos: usize,
+    /// The offset in the stream of the start of `buf`.
+    offset: u64,
+    eof: bool,
+}
+
+impl<R: Read, T: WideRepr> StreamSearcher<R, T>
+where
+    T::Repr: SwapBytes,
+{
+    /// Creates a searcher for a single code unit in a stream.
+    pub fn new(reader: R, needle: T, endianness: Endianness) -> StreamSearcher<R, T> {
+        StreamSearcher::substring(reader, &[needle], endianness)
+    }
+
+    /// Creates a searcher for a substring of code units in a stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the needle is empty.
+    pub fn substring(reader: R, needle: &[T], endianness: Endianness) -> StreamSearcher<R, T> {
+        assert!(!needle.is_empty(), "the needle must not be empty");
+
+        // Search the stream in its own byte order, by swapping the byte order
+        // of the needle rather than the stream.
+        let needle = as_repr_slice(needle);
+        let needle: Box<[T::Repr]> = if endianness == Endianness::NATIVE {
+            needle.into()
+        } else {
+            needle.iter().map(|&c| c.swap_bytes()).collect()
+        };
+
+        // A chunk must hold a needle that spans its boundary, with room to
+        // read more of the stream.
+        let capacity = (CHUNK_SIZE / mem::size_of::<T::Repr>()).max(2 * needle.len());
+        let filler = needle[0];
+
+        StreamSearcher {
+            reader,
+            needle,
+            buf: vec![filler; capacity].into_boxed_slice(),
+            len: 0,
+            partial: 0,
+            pos: 0,
+            offset: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Unwraps the searcher, returning the underlying reader.
+    ///
+    /// Any data read from the reader, but not yet searched, is lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Searches the code units in the chunk that can start a whole needle.
+    fn search(&mut self) -> Option<u64> {
+        let (&first, rest) = self.needle.split_first().unwrap();
+        let end = self.len.saturating_sub(rest.len());
+
+        while self.pos < end {
+            let pos = match crate::wmemchr(first, &self.buf[self.pos..end]) {
+                Some(i) => self.pos + i,
+                None => {
+                    self.pos = end;
+                    return None;
+                }
+            };
+            self.pos = pos + 1;
+
+            if self.buf[pos + 1..pos + 1 + rest.len()] == *rest {
+                return Some(self.offset + pos as u64);
+            }
+        }
+
+        None
+    }
+
+    /// Reads the next part of the stream into the chunk, keeping the code
+    /// units that may start a needle spanning the boundary.
+    fn refill(&mut self) -> io::Result<()> {
+        let size = mem::size_of::<T::Repr>();
+
+        let keep = self.pos.max(self.len.saturating_sub(self.needle.len() - 1));
+        let kept_bytes = (self.len - keep) * size + self.partial;
+
+        // SAFETY: `SwapBytes` is only implemented for integer types, for which
+        //         any bit pattern is valid.
+        let bytes = unsafe {
+            slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut u8, self.buf.len() * size)
+        };
+
+        // SAFETY: The kept bytes are within the chunk.
+        unsafe {
+            ptr::copy(
+                bytes.as_ptr().add(keep * size),
+                bytes.as_mut_ptr(),
+                kept_bytes,
+            );
+        }
+
+        self.offset += keep as u64;
+        self.len -= keep;
+        self.pos = 0;
+
+        let n = loop {
+            match self.reader.read(&mut bytes[kept_bytes..]) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+
+        // The chunk is left consistent if the read failed, so that the search
+        // can be resumed.
+        let n = n?;
+        if n == 0 {
+            self.eof = true;
+        }
+
+        let filled = kept_bytes + n;
+        self.len = filled / size;
+        self.partial = filled % size;
+
+        Ok(())
+    }
+}
+
+impl<R: Read, T: WideRepr> Iterator for StreamSearcher<R, T>
+where
+    T::Repr: SwapBytes,
+{
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<io::Result<u64>> {
+        loop {
+            if let Some(offset) = self.search() {
+                return Some(Ok(offset));
+            }
+
+            if self.eof {
+                return None;
+            }
+
+            if let Err(err) = self.refill() {
+                return Some(Err(err));
+            }
+        }
+    }
+}
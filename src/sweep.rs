@@ -0,0 +1,207 @@
+//! The exhaustive sweep over haystacks shared by [`check_kernel`] and the
+//! `testing` module.
+//!
+//! [`check_kernel`]: crate::check_kernel
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::mem;
+
+use crate::char::WideRepr;
+
+/// The size of the largest haystack checked, in bytes, which is four 512-bit
+/// vectors.
+pub const MAX_SIZE: usize = 256;
+
+/// The number of start offsets checked, in bytes, which is the alignment of a
+/// 512-bit vector.
+pub const ALIGN: usize = 64;
+
+/// The size of the largest huge haystack checked, in bytes, which is past two
+/// iterations of the loop unrolled over eight 256-bit vectors that the AVX2
+/// and EVEX kernels search haystacks of at least 1024 bytes with.
+pub const MAX_HUGE_SIZE: usize = 2048;
+
+/// Runs a search function and a reference implementation over haystacks of
+/// every length up to [`MAX_SIZE`] bytes, starting at every element offset
+/// within [`ALIGN`] bytes, panicking on the first disagreement.
+///
+/// Huge haystacks, longer than [`MAX_SIZE`] bytes and up to [`MAX_HUGE_SIZE`]
+/// bytes, are checked at fewer lengths and offsets, since every length would
+/// take too long. Their lengths step by a 512-bit vector and an element, so
+/// that they end at different offsets within a vector.
+///
+/// The haystacks are filled with a wide character other than the needle, and
+/// the needle is placed alone in each position, followed by another needle at
+/// the end, and preceded by another needle at the start. So this checks
+/// forward and reverse searches, and counts, alike.
+///
+/// With the `testing` feature on Unix platforms, haystacks are also placed
+/// directly before and after a guard page.
+///
+/// # Panics
+///
+/// Panics with `op`, `reference`, and the length, offset, placement and
+/// positions of the needles in the haystack on the first disagreement.
+pub(crate) fn sweep<T, R, F, E>(op: &str, reference: &str, search: F, expected: E)
+where
+    T: WideRepr + TryFrom<u8>,
+    R: fmt::Debug + PartialEq,
+    F: Fn(T, &[T]) -> R,
+    E: Fn(T, &[T]) -> R,
+{
+    let value = |v: u8| T::try_from(v).ok().unwrap();
+
+    let filler = value(b'a');
+    let needle = value(b'x');
+
+    let max_len = MAX_SIZE / mem::size_of::<T>();
+    let max_huge_len = MAX_HUGE_SIZE / mem::size_of::<T>();
+    let offsets = ALIGN / mem::size_of::<T>();
+
+    let huge_lens = || (max_len + 1..=max_huge_len).step_by(offsets + 1);
+
+    let mut buf = [filler; MAX_HUGE_SIZE + ALIGN];
+
+    let check = |haystack: &mut [T], placement: &str, offset: usize| {
+        check_haystack(haystack, filler, needle, |haystack, positions| {
+            assert_eq!(
+                search(needle, haystack),
+                expected(needle, haystack),
+                "{} disagrees with {}: len {}, offset {}, placement {}, needles at {:?}",
+                op,
+                reference,
+                haystack.len(),
+                offset,
+                placement,
+                positions,
+            );
+        });
+    };
+
+    for offset in 0..offsets {
+        for len in 0..=max_len {
+            check(&mut buf[offset..offset + len], "heap", offset);
+        }
+    }
+
+    // Huge haystacks start at the first, second and last element offsets.
+    for &offset in &[0, 1, offsets - 1] {
+        for len in huge_lens() {
+            check(&mut buf[offset..offset + len], "heap", offset);
+        }
+    }
+
+    #[cfg(all(unix, feature = "testing"))]
+    {
+        let mut pages = guard::GuardedPage::new();
+
+        for len in (0..=max_len).chain(huge_lens()) {
+            check(pages.after_guard(len, filler), "after guard", 0);
+            check(pages.before_guard(len, filler), "before guard", 0);
+        }
+    }
+}
+
+/// Calls `check` with a haystack of `filler`, without a needle, and with the
+/// needle in each position, along with the positions of the needles.
+pub(crate) fn check_haystack<T, F>(haystack: &mut [T], filler: T, needle: T, mut check: F)
+where
+    T: Copy,
+    F: FnMut(&[T], &[usize]),
+{
+    let len = haystack.len();
+
+    let mut place = |positions: &[usize]| {
+        for &pos in positions {
+            haystack[pos] = needle;
+        }
+
+        check(haystack, positions);
+
+        for &pos in positions {
+            haystack[pos] = filler;
+        }
+    };
+
+    place(&[]);
+
+    for pos in 0..len {
+        place(&[pos]);
+        place(&[pos, len - 1]);
+        place(&[0, pos]);
+    }
+}
+
+#[cfg(all(unix, feature = "testing"))]
+pub(crate) mod guard {
+    use core::{ptr, slice};
+
+    use crate::char::WideRepr;
+
+    /// A readable and writable page of memory, between two guard pages that
+    /// cannot be accessed.
+    pub struct GuardedPage {
+        ptr: *mut u8,
+        page_size: usize,
+    }
+
+    impl GuardedPage {
+        pub fn new() -> GuardedPage {
+            unsafe {
+                let page_size = libc::sysconf(libc::_SC_PAGESIZE) as usize;
+                assert!(page_size >= super::MAX_HUGE_SIZE + super::ALIGN);
+
+                let ptr = libc::mmap(
+                    ptr::null_mut(),
+                    3 * page_size,
+                    libc::PROT_NONE,
+                    libc::MAP_PRIVATE | libc::MAP_ANON,
+                    -1,
+                    0,
+                );
+                assert_ne!(ptr, libc::MAP_FAILED, "failed to map guard pages");
+
+                let ptr = ptr as *mut u8;
+                let ret = libc::mprotect(
+                    ptr.add(page_size) as *mut libc::c_void,
+                    page_size,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                );
+                assert_eq!(ret, 0, "failed to unprotect page");
+
+                GuardedPage { ptr, page_size }
+            }
+        }
+
+        /// Returns a slice of `len` elements of `filler`, starting at the start
+        /// of the page.
+        pub fn after_guard<T: WideRepr>(&mut self, len: usize, filler: T) -> &mut [T] {
+            unsafe { self.fill(self.ptr.add(self.page_size) as *mut T, len, filler) }
+        }
+
+        /// Returns a slice of `len` elements of `filler`, ending at the end of
+        /// the page.
+        pub fn before_guard<T: WideRepr>(&mut self, len: usize, filler: T) -> &mut [T] {
+            unsafe {
+                let end = self.ptr.add(2 * self.page_size) as *mut T;
+                self.fill(end.sub(len), len, filler)
+            }
+        }
+
+        unsafe fn fill<T: WideRepr>(&mut self, start: *mut T, len: usize, filler: T) -> &mut [T] {
+            for i in 0..len {
+                start.add(i).write(filler);
+            }
+            slice::from_raw_parts_mut(start, len)
+        }
+    }
+
+    impl Drop for GuardedPage {
+        fn drop(&mut self) {
+            unsafe {
+                libc::munmap(self.ptr as *mut libc::c_void, 3 * self.page_size);
+            }
+        }
+    }
+}
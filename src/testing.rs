@@ -0,0 +1,233 @@
+//! Exhaustive checks of search functions, for testing the crate's kernels and
+//! wrappers around them.
+//!
+//! Each check runs a search function over haystacks of every length up to
+//! [`MAX_SIZE`] bytes, starting at every element offset within [`ALIGN`]
+//! bytes, with the needle in every position, and compares the results with
+//! those of a reference implementation. Huge haystacks, of some lengths up to
+//! [`MAX_HUGE_SIZE`] bytes, are also checked at a few offsets, except by
+//! [`check_bytes`].
+//!
+//! On Unix platforms, haystacks are also placed directly before and after a
+//! guard page, which is mapped without read or write access, so that a search
+//! reading outside of the haystack crashes rather than silently passing.
+//!
+//! This module is only available with the `testing` feature.
+
+use core::convert::TryFrom;
+use core::{fmt, mem, ptr};
+
+use crate::char::WideRepr;
+use crate::naive;
+use crate::sweep::{self, check_haystack};
+
+pub use crate::sweep::{ALIGN, MAX_HUGE_SIZE, MAX_SIZE};
+
+/// Checks a search for the first occurrence of a wide character against
+/// [`naive::wmemchr`], panicking on the first disagreement.
+///
+/// # Panics
+///
+/// Panics with the length, offset, placement and positions of the needles in
+/// the haystack on the first disagreement.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::testing::check_wmemchr;
+///
+/// check_wmemchr(|needle: u16, haystack: &[u16]| wmemchr::wmemchr(needle, haystack));
+/// ```
+pub fn check_wmemchr<T, F>(search: F)
+where
+    T: WideRepr + TryFrom<u8>,
+    F: Fn(T, &[T]) -> Option<usize>,
+{
+    sweep::sweep("wmemchr", "naive", search, naive::wmemchr)
+}
+
+/// Checks a search for the last occurrence of a wide character against
+/// [`naive::wmemrchr`], panicking on the first disagreement.
+///
+/// # Panics
+///
+/// Panics with the length, offset, placement and positions of the needles in
+/// the haystack on the first disagreement.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::testing::check_wmemrchr;
+///
+/// check_wmemrchr(|needle: u16, haystack: &[u16]| wmemchr::wmemrchr(needle, haystack));
+/// ```
+pub fn check_wmemrchr<T, F>(search: F)
+where
+    T: WideRepr + TryFrom<u8>,
+    F: Fn(T, &[T]) -> Option<usize>,
+{
+    sweep::sweep("wmemrchr", "naive", search, naive::wmemrchr)
+}
+
+/// Checks a count of the occurrences of a wide character against
+/// [`naive::wmemcount`], panicking on the first disagreement.
+///
+/// # Panics
+///
+/// Panics with the length, offset, placement and positions of the needles in
+/// the haystack on the first disagreement.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::testing::check_wmemcount;
+///
+/// check_wmemcount(|needle: u16, haystack: &[u16]| wmemchr::wmemcount(needle, haystack));
+/// ```
+pub fn check_wmemcount<T, F>(search: F)
+where
+    T: WideRepr + TryFrom<u8>,
+    F: Fn(T, &[T]) -> usize,
+{
+    sweep::sweep("wmemcount", "naive", search, naive::wmemcount)
+}
+
+/// Checks a search function against a reference implementation, panicking on
+/// the first disagreement.
+///
+/// The haystacks are filled with a wide character other than the needle, and
+/// the needle is placed alone in each position, followed by another needle at
+/// the end, and preceded by another needle at the start. So this checks
+/// forward and reverse searches, and counts, alike.
+///
+/// # Panics
+///
+/// Panics with the length, offset, placement and positions of the needles in
+/// the haystack on the first disagreement.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::testing::check_search;
+///
+/// check_search(
+///     |needle: u32, haystack: &[u32]| wmemchr::wmemchr_any(&[needle], haystack),
+///     wmemchr::naive::wmemchr,
+/// );
+/// ```
+pub fn check_search<T, R, F, E>(search: F, expected: E)
+where
+    T: WideRepr + TryFrom<u8>,
+    R: fmt::Debug + PartialEq,
+    F: Fn(T, &[T]) -> R,
+    E: Fn(T, &[T]) -> R,
+{
+    sweep::sweep("search", "reference", search, expected)
+}
+
+/// Checks a search for the first occurrence of a wide character in a byte
+/// slice of native endian code units against [`naive::wmemchr`], panicking on
+/// the first disagreement.
+///
+/// The haystacks start at every byte offset within [`ALIGN`] bytes, so that
+/// the code units are not aligned, and end with a partial code unit, which
+/// must be ignored, as well as without one.
+///
+/// # Panics
+///
+/// Panics with the length, offset, placement and positions of the needles in
+/// the haystack on the first disagreement.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use wmemchr::testing::check_bytes;
+///
+/// check_bytes(|needle: u16, haystack: &[u8]| {
+///     wmemchr::wmemchr_bytes_le(u16::from_le(needle), haystack)
+/// });
+/// ```
+pub fn check_bytes<T, F>(search: F)
+where
+    T: WideRepr + TryFrom<u8>,
+    F: Fn(T, &[u8]) -> Option<usize>,
+{
+    let value = |v: u8| T::try_from(v).ok().unwrap();
+
+    let filler = value(b'a');
+    let needle = value(b'x');
+
+    let size = mem::size_of::<T>();
+    let max_len = MAX_SIZE / size;
+
+    // A partial code unit is fewer bytes than a full one, so the haystack at
+    // every offset fits within the buffer.
+    let partials: &[usize] = if size == 1 { &[0] } else { &[0, size - 1] };
+
+    let mut units = [filler; MAX_SIZE];
+    let mut buf = [0; MAX_SIZE + 2 * ALIGN];
+
+    let mut check = |bytes: &mut [u8], placement: &str, offset: usize, partial: usize| {
+        let len = (bytes.len() - partial) / size;
+
+        check_haystack(&mut units[..len], filler, needle, |units, positions| {
+            // SAFETY: `bytes` holds `len` code units and `partial` bytes.
+            unsafe {
+                let dst = bytes.as_mut_ptr();
+                for (i, &unit) in units.iter().enumerate() {
+                    ptr::write_unaligned(dst.add(i * size) as *mut T, unit);
+                }
+                // Start a code unit of the needle, which must not be matched.
+                ptr::copy_nonoverlapping(
+                    &needle as *const T as *const u8,
+                    dst.add(len * size),
+                    partial,
+                );
+            }
+
+            assert_eq!(
+                search(needle, bytes),
+                naive::wmemchr(needle, units),
+                "bytes search disagrees with naive: len {}, offset {}, partial {}, \
+                 placement {}, needles at {:?}",
+                len,
+                offset,
+                partial,
+                placement,
+                positions,
+            );
+        });
+    };
+
+    for offset in 0..ALIGN {
+        for len in 0..=max_len {
+            for &partial in partials {
+                let end = offset + len * size + partial;
+                check(&mut buf[offset..end], "heap", offset, partial);
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        let mut pages = sweep::guard::GuardedPage::new();
+
+        for len in 0..=max_len {
+            for &partial in partials {
+                let bytes = len * size + partial;
+                check(pages.after_guard(bytes, 0), "after guard", 0, partial);
+                check(pages.before_guard(bytes, 0), "before guard", 0, partial);
+            }
+        }
+    }
+}
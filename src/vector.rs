@@ -0,0 +1,601 @@
+//! A vector abstraction over the SIMD and SWAR types used by the kernels, so
+//! that each search operation is written once for every backend.
+
+use core::num::NonZeroU64;
+use core::{mem, ptr};
+
+/// A vector of `LANES` elements of type `T`.
+///
+/// The methods are only safe to call when the CPU supports the instructions
+/// used to implement them, and are always inlined so that they are compiled
+/// with the target features of the kernel calling them.
+///
+/// # Safety
+///
+/// `Self` must be exactly `LANES` elements of `T` in size, and
+/// [`movemask`](Vector::movemask) must only set bits within the `MASK_STRIDE`
+/// bits of lanes that are set in the mask, with the first element in the
+/// lowest bits, setting exactly `MASK_BITS` bits for each of them.
+pub(crate) unsafe trait Vector<T>: Copy {
+    /// The number of elements in the vector.
+    const LANES: usize;
+    /// The number of bits used by each element in a movemask.
+    const MASK_STRIDE: usize;
+    /// The number of bits set in a movemask for each element that is set.
+    const MASK_BITS: u32;
+
+    /// The result of comparing two vectors.
+    type Mask: Copy;
+
+    /// Broadcasts a value across the elements of a vector.
+    unsafe fn splat(value: T) -> Self;
+
+    /// Loads a vector from a pointer aligned to the size of the vector.
+    unsafe fn load_aligned(ptr: *const T) -> Self;
+
+    /// Loads a vector from a pointer with no alignment requirement.
+    unsafe fn load_unaligned(ptr: *const T) -> Self;
+
+    /// Compares the elements of two vectors for equality.
+    unsafe fn cmpeq(self, other: Self) -> Self::Mask;
+
+    /// Combines the set lanes of two masks.
+    unsafe fn or(a: Self::Mask, b: Self::Mask) -> Self::Mask;
+
+    /// Packs a mask into an integer, using `MASK_STRIDE` bits for each element.
+    unsafe fn movemask(mask: Self::Mask) -> u64;
+}
+
+/// Searches a haystack of at least one vector for the first occurrence of
+/// `needle`, aligning the pointer so that the main loop uses aligned loads.
+///
+/// Haystacks of at least `huge` bytes start with a loop unrolled over 8
+/// vectors.
+#[inline(always)]
+pub(crate) unsafe fn wmemchr<T, V: Vector<T>>(
+    needle: T,
+    haystack: *const T,
+    len: usize,
+    huge: Option<usize>,
+) -> Option<usize> {
+    let v_needle = V::splat(needle);
+    forward_search(haystack, len, true, huge, |chunk: V| chunk.cmpeq(v_needle))
+}
+
+/// Like [`wmemchr`], except that `haystack` need only be aligned to a byte
+/// boundary, rather than to the size of an element.
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline(always)]
+pub(crate) unsafe fn wmemchr_unaligned<T, V: Vector<T>>(
+    needle: T,
+    haystack: *const T,
+    len: usize,
+) -> Option<usize> {
+    let v_needle = V::splat(needle);
+    forward_search(haystack, len, false, None, |chunk: V| chunk.cmpeq(v_needle))
+}
+
+/// Searches a haystack of at least one and fewer than two vectors, with two
+/// overlapping loads, and without branching on the contents of the haystack.
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline(always)]
+pub(crate) unsafe fn wmemchr_pair<T, V: Vector<T>>(
+    needle: T,
+    haystack: *const T,
+    len: usize,
+) -> Option<usize> {
+    debug_assert!((V::LANES..2 * V::LANES).contains(&len));
+    debug_assert!(2 * V::LANES * V::MASK_STRIDE <= 64);
+
+    let v_needle = V::splat(needle);
+
+    let first = V::movemask(V::load_unaligned(haystack).cmpeq(v_needle));
+    let last = V::movemask(V::load_unaligned(haystack.add(len - V::LANES)).cmpeq(v_needle));
+
+    // Shift the matches in the last load to their offset in the haystack.
+    let mask = first | (last << ((len - V::LANES) * V::MASK_STRIDE));
+
+    NonZeroU64::new(mask).map(forward_pos::<T, V>)
+}
+
+/// Searches a haystack of any length for the first element for which `eq` sets
+/// the lane of the vector containing it.
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline(always)]
+pub(crate) unsafe fn search_by<T, V, F>(haystack: *const T, len: usize, eq: F) -> Option<usize>
+where
+    V: Vector<T>,
+    F: Fn(V) -> V::Mask,
+{
+    // If haystack length is less than number of elements in a vector, then
+    // copy it into a vector, and discard the lanes past its end.
+    if len < V::LANES {
+        let mask = V::movemask(eq(load_partial::<T, V>(haystack, len))) & valid::<T, V>(len);
+        return NonZeroU64::new(mask).map(forward_pos::<T, V>);
+    }
+
+    forward_search(haystack, len, true, None, eq)
+}
+
+/// Searches a haystack of any length for the last occurrence of `needle`.
+#[inline(always)]
+pub(crate) unsafe fn wmemrchr<T, V: Vector<T>>(
+    needle: T,
+    haystack: *const T,
+    len: usize,
+) -> Option<usize> {
+    let v_needle = V::splat(needle);
+    reverse_search_by(haystack, len, |chunk: V| chunk.cmpeq(v_needle))
+}
+
+/// Counts the occurrences of `needle` in a haystack of any length.
+#[inline(always)]
+pub(crate) unsafe fn wmemcount<T, V: Vector<T>>(
+    needle: T,
+    haystack: *const T,
+    len: usize,
+) -> usize {
+    let v_needle = V::splat(needle);
+    let eq = |chunk: V| chunk.cmpeq(v_needle);
+    let count = |mask: u64| (mask.count_ones() / V::MASK_BITS) as usize;
+
+    let mut ptr = haystack;
+    let mut remaining = len;
+    let mut total = 0;
+
+    // 4 vector loop.
+    while remaining >= 4 * V::LANES {
+        total += count(V::movemask(eq(V::load_unaligned(ptr))));
+        total += count(V::movemask(eq(V::load_unaligned(ptr.add(V::LANES)))));
+        total += count(V::movemask(eq(V::load_unaligned(ptr.add(2 * V::LANES)))));
+        total += count(V::movemask(eq(V::load_unaligned(ptr.add(3 * V::LANES)))));
+
+        ptr = ptr.add(4 * V::LANES);
+        remaining -= 4 * V::LANES;
+    }
+
+    // 1 vector loop.
+    while remaining >= V::LANES {
+        total += count(V::movemask(eq(V::load_unaligned(ptr))));
+
+        ptr = ptr.add(V::LANES);
+        remaining -= V::LANES;
+    }
+
+    // Elements must not be counted twice, so the remaining elements are copied
+    // into a vector, discarding the lanes past the end of the haystack.
+    if remaining > 0 {
+        total +=
+            count(V::movemask(eq(load_partial::<T, V>(ptr, remaining))) & valid::<T, V>(remaining));
+    }
+
+    total
+}
+
+/// Returns the number of elements before the first null element of a string,
+/// or `maxlen` if none of the first `maxlen` elements is null.
+#[cfg(not(miri))]
+#[inline(always)]
+pub(crate) unsafe fn wcsnlen<T, V: Vector<T>>(s: *const T, maxlen: usize) -> usize {
+    let v_nul = V::splat(mem::zeroed());
+    wcsnlen_by(s, maxlen, |chunk: V| chunk.cmpeq(v_nul))
+}
+
+/// Returns the index of the first occurrence of `needle` in a string, or of
+/// its null terminator if `needle` does not occur before it.
+#[cfg(not(miri))]
+#[inline(always)]
+pub(crate) unsafe fn wcschr<T, V: Vector<T>>(s: *const T, needle: T) -> usize {
+    let v_nul = V::splat(mem::zeroed());
+    let v_needle = V::splat(needle);
+    wcsnlen_by(s, usize::MAX, |chunk: V| {
+        V::or(chunk.cmpeq(v_nul), chunk.cmpeq(v_needle))
+    })
+}
+
+/// Returns the index of the first element of a string for which `eq` sets the
+/// lane of the vector containing it, which must include its null terminator,
+/// or `maxlen` if there is none within the first `maxlen` elements.
+///
+/// Every load is aligned to the size of a vector, so that no load crosses a
+/// page boundary, as in glibc. The first load starts at the vector containing
+/// the start of the string, discarding the lanes before it, and the search
+/// stops at the vector containing the element found or the `maxlen`th
+/// element.
+#[cfg(not(miri))]
+#[inline(always)]
+unsafe fn wcsnlen_by<T, V, F>(s: *const T, maxlen: usize, eq: F) -> usize
+where
+    V: Vector<T>,
+    F: Fn(V) -> V::Mask,
+{
+    // If `maxlen` is zero, then `s` may be null.
+    if maxlen == 0 {
+        return 0;
+    }
+
+    // The number of lanes before the start of the string in its vector.
+    let skip = ((s as usize) & (mem::size_of::<V>() - 1)) / mem::size_of::<T>();
+
+    let mask = V::movemask(eq(V::load_aligned(s.wrapping_sub(skip)))) >> (skip * V::MASK_STRIDE);
+
+    if let Some(mask) = NonZeroU64::new(mask) {
+        return forward_pos::<T, V>(mask).min(maxlen);
+    }
+
+    let loop_size = 4 * mem::size_of::<V>();
+
+    // The number of elements searched, none of which were found.
+    let mut len = V::LANES - skip;
+
+    // 1 vector loop, until the 4 vector loop is aligned to its size, so that
+    // it does not cross a page boundary either.
+    while len < maxlen && (s.add(len) as usize) & (loop_size - 1) != 0 {
+        if let Some(mask) = NonZeroU64::new(V::movemask(eq(V::load_aligned(s.add(len))))) {
+            return (len + forward_pos::<T, V>(mask)).min(maxlen);
+        }
+
+        len += V::LANES;
+    }
+
+    // 4 vector loop.
+    while len < maxlen {
+        let ptr = s.add(len);
+
+        let eq_a = eq(V::load_aligned(ptr));
+        let eq_b = eq(V::load_aligned(ptr.add(V::LANES)));
+        let eq_c = eq(V::load_aligned(ptr.add(2 * V::LANES)));
+        let eq_d = eq(V::load_aligned(ptr.add(3 * V::LANES)));
+
+        // Determine if any vectors contained an element found.
+        let or = V::or(V::or(eq_a, eq_b), V::or(eq_c, eq_d));
+
+        if V::movemask(or) != 0 {
+            for (i, &eq) in [eq_a, eq_b, eq_c].iter().enumerate() {
+                if let Some(mask) = NonZeroU64::new(V::movemask(eq)) {
+                    return (len + i * V::LANES + forward_pos::<T, V>(mask)).min(maxlen);
+                }
+            }
+
+            let mask = V::movemask(eq_d);
+            debug_assert_ne!(mask, 0);
+            let mask = NonZeroU64::new_unchecked(mask);
+            return (len + 3 * V::LANES + forward_pos::<T, V>(mask)).min(maxlen);
+        }
+
+        len += 4 * V::LANES;
+    }
+
+    maxlen
+}
+
+/// Searches a haystack of any length for the last element for which `eq` sets
+/// the lane of the vector containing it.
+#[inline(always)]
+pub(crate) unsafe fn reverse_search_by<T, V, F>(
+    haystack: *const T,
+    len: usize,
+    eq: F,
+) -> Option<usize>
+where
+    V: Vector<T>,
+    F: Fn(V) -> V::Mask,
+{
+    let vector_size = mem::size_of::<V>();
+    let loop_size = 4 * vector_size;
+
+    // If haystack length is less than number of elements in a vector, then
+    // copy it into a vector, and discard the lanes past its end.
+    if len < V::LANES {
+        let mask = V::movemask(eq(load_partial::<T, V>(haystack, len))) & valid::<T, V>(len);
+        return NonZeroU64::new(mask).map(reverse_pos::<T, V>);
+    }
+
+    let start = haystack;
+    let end = haystack.add(len);
+
+    if let Some(pos) = reverse_chunk::<T, V>(
+        start,
+        end.sub(V::LANES),
+        eq(V::load_unaligned(end.sub(V::LANES))),
+    ) {
+        return Some(pos);
+    }
+
+    // Align `ptr` down to improve read performance in loop, searching
+    // backwards from it. Since `end` is aligned to an element, this moves
+    // back less than one vector, so `ptr` is within the haystack.
+    let mut ptr = ((end as usize) & !(vector_size - 1)) as *const T;
+
+    debug_assert!(start <= ptr && ptr <= end);
+
+    // 4 vector loop.
+    while (ptr as usize) >= (start as usize) + loop_size {
+        ptr = ptr.sub(4 * V::LANES);
+
+        let eq_a = eq(V::load_aligned(ptr));
+        let eq_b = eq(V::load_aligned(ptr.add(V::LANES)));
+        let eq_c = eq(V::load_aligned(ptr.add(2 * V::LANES)));
+        let eq_d = eq(V::load_aligned(ptr.add(3 * V::LANES)));
+
+        // Determine if any vectors contained the needle.
+        let or = V::or(V::or(eq_a, eq_b), V::or(eq_c, eq_d));
+
+        // If any vector contains the needle, we will search for it in each
+        // vector, starting from the last.
+        if V::movemask(or) != 0 {
+            let offset = ptr.offset_from(start) as usize;
+
+            if let Some(mask) = NonZeroU64::new(V::movemask(eq_d)) {
+                return Some(offset + 3 * V::LANES + reverse_pos::<T, V>(mask));
+            }
+            if let Some(mask) = NonZeroU64::new(V::movemask(eq_c)) {
+                return Some(offset + 2 * V::LANES + reverse_pos::<T, V>(mask));
+            }
+            if let Some(mask) = NonZeroU64::new(V::movemask(eq_b)) {
+                return Some(offset + V::LANES + reverse_pos::<T, V>(mask));
+            }
+
+            let mask = V::movemask(eq_a);
+            debug_assert_ne!(mask, 0);
+            let mask = NonZeroU64::new_unchecked(mask);
+            return Some(offset + reverse_pos::<T, V>(mask));
+        }
+    }
+
+    // 1 vector loop.
+    while (ptr as usize) >= (start as usize) + vector_size {
+        ptr = ptr.sub(V::LANES);
+
+        if let Some(pos) = reverse_chunk::<T, V>(start, ptr, eq(V::load_aligned(ptr))) {
+            return Some(pos);
+        }
+    }
+
+    // Invariant: `0 <= ptr - start < V::LANES`.
+
+    // We can search the remaining elements by doing an unaligned search of the
+    // first vector of the haystack.
+
+    if start < ptr {
+        return reverse_chunk::<T, V>(start, start, eq(V::load_unaligned(start)));
+    }
+
+    None
+}
+
+/// Copies a haystack of fewer elements than a vector into a zeroed vector.
+#[inline(always)]
+unsafe fn load_partial<T, V: Vector<T>>(haystack: *const T, len: usize) -> V {
+    debug_assert!(len < V::LANES);
+
+    let mut chunk: V = mem::zeroed();
+    ptr::copy_nonoverlapping(haystack, &mut chunk as *mut V as *mut T, len);
+    chunk
+}
+
+/// Returns a movemask of the first `len` elements of a vector.
+#[inline(always)]
+fn valid<T, V: Vector<T>>(len: usize) -> u64 {
+    debug_assert!(len < V::LANES);
+
+    (1u64 << (len * V::MASK_STRIDE)) - 1
+}
+
+/// Searches a haystack of at least one vector for the first element for which
+/// `eq` sets the lane of the vector containing it.
+#[inline(always)]
+unsafe fn forward_search<T, V, F>(
+    haystack: *const T,
+    len: usize,
+    aligned: bool,
+    huge: Option<usize>,
+    eq: F,
+) -> Option<usize>
+where
+    V: Vector<T>,
+    F: Fn(V) -> V::Mask,
+{
+    let vector_size = mem::size_of::<V>();
+    let loop_size = 4 * vector_size;
+    let huge_loop_size = 8 * vector_size;
+
+    let start = haystack;
+    let end = haystack.add(len);
+    let mut ptr = start;
+
+    debug_assert!(start <= end);
+    debug_assert!(len >= V::LANES);
+
+    let load = |ptr: *const T| {
+        if aligned {
+            debug_assert_eq!((ptr as usize) % vector_size, 0);
+            V::load_aligned(ptr)
+        } else {
+            V::load_unaligned(ptr)
+        }
+    };
+
+    if aligned {
+        if let Some(pos) = forward_chunk::<T, V>(start, ptr, eq(V::load_unaligned(ptr))) {
+            return Some(pos);
+        }
+
+        // Align `ptr` to improve read performance in loop.
+        // This calculation is based on byte pointer, and not the scaled addition.
+        ptr = {
+            let align_offset = vector_size - ((start as usize) & (vector_size - 1));
+            (start as *const u8).add(align_offset) as *const T
+        };
+
+        // The pointer will advance at least one element and at most by the
+        // number of elements in one vector.
+        debug_assert!(start < ptr);
+        debug_assert!(ptr.offset_from(start) as usize <= V::LANES);
+    }
+
+    // 8 vector loop, for huge haystacks.
+    //
+    // When the needle is found, the search falls through to the narrower loop,
+    // which finds the needle within the next two iterations.
+    if matches!(huge, Some(huge) if len * mem::size_of::<T>() >= huge) {
+        if let Some(loop_end) = (end as usize).checked_sub(huge_loop_size) {
+            while (ptr as usize) <= loop_end {
+                let or_abcd = V::or(
+                    V::or(eq(load(ptr)), eq(load(ptr.add(V::LANES)))),
+                    V::or(
+                        eq(load(ptr.add(2 * V::LANES))),
+                        eq(load(ptr.add(3 * V::LANES))),
+                    ),
+                );
+                let or_efgh = V::or(
+                    V::or(
+                        eq(load(ptr.add(4 * V::LANES))),
+                        eq(load(ptr.add(5 * V::LANES))),
+                    ),
+                    V::or(
+                        eq(load(ptr.add(6 * V::LANES))),
+                        eq(load(ptr.add(7 * V::LANES))),
+                    ),
+                );
+
+                if V::movemask(V::or(or_abcd, or_efgh)) != 0 {
+                    break;
+                }
+
+                ptr = ptr.add(8 * V::LANES);
+            }
+        }
+    }
+
+    // 4 vector loop.
+    if let Some(loop_end) = (end as usize).checked_sub(loop_size) {
+        while (ptr as usize) <= loop_end {
+            // Look for needle in 4 vectors.
+            let eq_a = eq(load(ptr));
+            let eq_b = eq(load(ptr.add(V::LANES)));
+            let eq_c = eq(load(ptr.add(2 * V::LANES)));
+            let eq_d = eq(load(ptr.add(3 * V::LANES)));
+
+            // Determine if any vectors contained the needle.
+            let or = V::or(V::or(eq_a, eq_b), V::or(eq_c, eq_d));
+
+            // If any vector contains the needle, we will search for it in each vector.
+            if V::movemask(or) != 0 {
+                // Keep track of the offset from the start of the haystack.
+                let mut offset = ptr.offset_from(start) as usize;
+
+                if let Some(mask) = NonZeroU64::new(V::movemask(eq_a)) {
+                    return Some(offset + forward_pos::<T, V>(mask));
+                }
+                offset += V::LANES;
+
+                if let Some(mask) = NonZeroU64::new(V::movemask(eq_b)) {
+                    return Some(offset + forward_pos::<T, V>(mask));
+                }
+                offset += V::LANES;
+
+                if let Some(mask) = NonZeroU64::new(V::movemask(eq_c)) {
+                    return Some(offset + forward_pos::<T, V>(mask));
+                }
+                offset += V::LANES;
+
+                let mask = V::movemask(eq_d);
+                debug_assert_ne!(mask, 0);
+                let mask = NonZeroU64::new_unchecked(mask);
+                return Some(offset + forward_pos::<T, V>(mask));
+            }
+
+            ptr = ptr.add(4 * V::LANES);
+        }
+    }
+
+    // 1 vector loop.
+    if let Some(loop_end) = (end as usize).checked_sub(vector_size) {
+        while (ptr as usize) <= loop_end {
+            if let Some(pos) = forward_chunk::<T, V>(start, ptr, eq(load(ptr))) {
+                return Some(pos);
+            }
+
+            ptr = ptr.add(V::LANES);
+        }
+    }
+
+    // Invariant: `0 <= end - ptr < V::LANES`.
+
+    // We can search the remaining elements by shifting `ptr` back and doing an
+    // unaligned forward search.
+
+    if ptr < end {
+        ptr = end.sub(V::LANES);
+
+        return forward_chunk::<T, V>(start, ptr, eq(V::load_unaligned(ptr)));
+    }
+
+    None
+}
+
+/// Returns the offset from `start` of the first set lane in the mask of the
+/// vector loaded from `ptr`.
+#[inline(always)]
+unsafe fn forward_chunk<T, V: Vector<T>>(
+    start: *const T,
+    ptr: *const T,
+    mask: V::Mask,
+) -> Option<usize> {
+    debug_assert!(start <= ptr);
+
+    NonZeroU64::new(V::movemask(mask)).map(|mask| {
+        let offset = ptr.offset_from(start) as usize;
+        offset + forward_pos::<T, V>(mask)
+    })
+}
+
+/// Get the forward position in a mask obtained from [`Vector::movemask`].
+///
+/// # Notes
+///
+/// We take advantage of the fact that the mask is non-zero, in order to
+/// optimise this function.
+#[inline(always)]
+fn forward_pos<T, V: Vector<T>>(mask: NonZeroU64) -> usize {
+    #[cfg(rustc_1_53)]
+    let zeros = mask.trailing_zeros();
+    #[cfg(not(rustc_1_53))]
+    let zeros = match mask.get() {
+        0 => unsafe { core::hint::unreachable_unchecked() },
+        mask => mask.trailing_zeros(),
+    };
+
+    zeros as usize / V::MASK_STRIDE
+}
+
+/// Returns the offset from `start` of the last set lane in the mask of the
+/// vector loaded from `ptr`.
+#[inline(always)]
+unsafe fn reverse_chunk<T, V: Vector<T>>(
+    start: *const T,
+    ptr: *const T,
+    mask: V::Mask,
+) -> Option<usize> {
+    debug_assert!(start <= ptr);
+
+    NonZeroU64::new(V::movemask(mask)).map(|mask| {
+        let offset = ptr.offset_from(start) as usize;
+        offset + reverse_pos::<T, V>(mask)
+    })
+}
+
+/// Get the reverse position in a mask obtained from [`Vector::movemask`].
+#[inline(always)]
+fn reverse_pos<T, V: Vector<T>>(mask: NonZeroU64) -> usize {
+    #[cfg(rustc_1_53)]
+    let zeros = mask.leading_zeros();
+    #[cfg(not(rustc_1_53))]
+    let zeros = match mask.get() {
+        0 => unsafe { core::hint::unreachable_unchecked() },
+        mask => mask.leading_zeros(),
+    };
+
+    (63 - zeros as usize) / V::MASK_STRIDE
+}
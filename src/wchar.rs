@@ -0,0 +1,157 @@
+//! Search functions with the signatures and semantics of their libc
+//! counterparts, over the platform's [`wchar_t`].
+//!
+//! These are implemented on top of the crate's kernels, and do not depend on
+//! libc.
+//!
+//! # Examples
+//!
+//! Basic usage:
+//!
+//! ```
+//! use wmemchr::wchar::{wchar_t, wcschr, wcslen};
+//!
+//! let s: Vec<wchar_t> = "foo bar\0".chars().map(|c| c as wchar_t).collect();
+//!
+//! unsafe {
+//!     assert_eq!(wcslen(s.as_ptr()), 7);
+//!     assert_eq!(wcschr(s.as_ptr(), 'b' as wchar_t), s.as_ptr().add(4) as *mut wchar_t);
+//! }
+//! ```
+
+#![allow(non_camel_case_types)]
+
+use core::{ptr, slice};
+
+use crate::raw::{self, wmemchr_raw};
+
+cfg_if::cfg_if! {
+    if #[cfg(windows)] {
+        /// The platform's wide character type, as in C.
+        pub type wchar_t = u16;
+    } else if #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "aarch64", target_arch = "arm"),
+    ))] {
+        /// The platform's wide character type, as in C.
+        pub type wchar_t = u32;
+    } else {
+        /// The platform's wide character type, as in C.
+        pub type wchar_t = i32;
+    }
+}
+
+/// Returns a pointer to the first occurrence of `c` in the `n` wide characters
+/// starting at `s`, or a null pointer if it is not found.
+///
+/// # Safety
+///
+/// Unless `n` is zero, `s` must be non-null, aligned, and valid for reads of
+/// `n` wide characters.
+#[inline]
+pub unsafe fn wmemchr(s: *const wchar_t, c: wchar_t, n: usize) -> *mut wchar_t {
+    wmemchr_raw(c, s, n) as *mut wchar_t
+}
+
+/// Returns the number of wide characters before the null terminator of the
+/// string `s`.
+///
+/// # Safety
+///
+/// `s` must be non-null, aligned, and valid for reads up to and including its
+/// null terminator.
+#[inline]
+pub unsafe fn wcslen(s: *const wchar_t) -> usize {
+    cfg_if::cfg_if! {
+        if #[cfg(windows)] {
+            raw::wcslen16(s)
+        } else {
+            raw::wcslen32(s as *const u32)
+        }
+    }
+}
+
+/// Returns a pointer to the first occurrence of `c` in the string `s`, or a
+/// null pointer if it is not found.
+///
+/// The null terminator is part of the string, so searching for a null wide
+/// character returns a pointer to the terminator.
+///
+/// # Safety
+///
+/// `s` must be non-null, aligned, and valid for reads up to and including its
+/// null terminator.
+#[inline]
+pub unsafe fn wcschr(s: *const wchar_t, c: wchar_t) -> *mut wchar_t {
+    cfg_if::cfg_if! {
+        if #[cfg(windows)] {
+            raw::wcschr16(s, c) as *mut wchar_t
+        } else {
+            raw::wcschr32(s as *const u32, c as u32) as *mut wchar_t
+        }
+    }
+}
+
+/// Returns a pointer to the first occurrence of the string `needle` in the
+/// string `haystack`, or a null pointer if it is not found.
+///
+/// An empty needle is found at the start of the haystack.
+///
+/// # Safety
+///
+/// `haystack` and `needle` must be non-null, aligned, and valid for reads up to
+/// and including their null terminators.
+pub unsafe fn wcsstr(haystack: *const wchar_t, needle: *const wchar_t) -> *mut wchar_t {
+    let haystack = slice::from_raw_parts(haystack, wcslen(haystack));
+    let needle = slice::from_raw_parts(needle, wcslen(needle));
+
+    let pos = find(haystack, needle);
+
+    #[cfg(feature = "checked")]
+    crate::checked::check::<wchar_t, _, _, _>(
+        "wcsstr",
+        false,
+        needle,
+        haystack,
+        pos,
+        match needle.len() {
+            0 => Some(0),
+            len => haystack.windows(len).position(|window| window == needle),
+        },
+    );
+
+    match pos {
+        Some(pos) => haystack.as_ptr().add(pos) as *mut wchar_t,
+        None => ptr::null_mut(),
+    }
+}
+
+/// Returns the index of the first occurrence of `needle` in `haystack`, or
+/// [`None`] if it is not found.
+fn find(haystack: &[wchar_t], needle: &[wchar_t]) -> Option<usize> {
+    let (&first, rest) = match needle.split_first() {
+        Some(split) => split,
+        None => return Some(0),
+    };
+
+    let mut pos = 0;
+
+    // Find each occurrence of the first wide character of the needle, until
+    // the rest of the needle no longer fits in the haystack.
+    while haystack.len() - pos >= needle.len() {
+        let candidates = &haystack[pos..haystack.len() - rest.len()];
+
+        match crate::wmemchr(first, candidates) {
+            Some(i) => pos += i,
+            None => break,
+        }
+
+        if &haystack[pos + 1..pos + needle.len()] == rest {
+            return Some(pos);
+        }
+
+        pos += 1;
+    }
+
+    None
+}
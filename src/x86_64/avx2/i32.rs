@@ -1,241 +1,109 @@
-use core::arch::x86_64::*;
 use core::mem;
-use core::num::NonZeroI32;
 
-use crate::x86_64::sse2::i32::forward_pos;
+use crate::vector;
+use crate::x86_64::arch::*;
+use crate::x86_64::HUGE_SIZE_I32;
+use crate::x86_64::sse2::i32::wmemchr_tiny;
 
 const VECTOR_SIZE: usize = mem::size_of::<__m256i>();
-const VECTOR_ALIGN: usize = VECTOR_SIZE - 1;
 
 const VECTOR_ELEMENTS: usize = VECTOR_SIZE / mem::size_of::<i32>();
 
-const LOOP_SIZE: usize = 4 * VECTOR_SIZE;
-const LOOP_ELEMENTS: usize = 4 * VECTOR_ELEMENTS;
-
-const SMALL_VECTOR_ELEMENTS: usize = mem::size_of::<__m128i>() / mem::size_of::<i32>();
-
+/// Searches a haystack by its size class: tiny haystacks, shorter than two
+/// vectors, are searched with two overlapping loads, while huge haystacks, of
+/// at least [`HUGE_SIZE_I32`] bytes, start with a loop unrolled over 8 vectors.
+#[cfg_attr(target_feature = "avx2", inline)]
 #[target_feature(enable = "avx2")]
 pub unsafe fn wmemchr(needle: i32, haystack: *const i32, len: usize) -> Option<usize> {
-    let start = haystack;
-    let end = haystack.add(len);
-
-    debug_assert!(start <= end);
-
-    // If haystack length is less than number of elements in a packed vector,
-    // then try with a smaller vector.
+    // If haystack is shorter than two vectors, then search it with two
+    // overlapping loads.
     if len < VECTOR_ELEMENTS {
-        return wmemchr_small(needle, start, end, len);
+        return wmemchr_tiny(needle, haystack, len);
     }
-
-    debug_assert!(end.offset_from(start) as usize >= VECTOR_ELEMENTS);
-
-    let mut ptr = start;
-
-    // Broadcast the needle across the elements of the vector.
-    let v_needle = _mm256_set1_epi32(needle);
-
-    if let Some(pos) = forward_search_unaligned(start, end, ptr, v_needle) {
-        return Some(pos);
+    if len < 2 * VECTOR_ELEMENTS {
+        return vector::wmemchr_pair::<i32, __m256i>(needle, haystack, len);
     }
 
-    // Align `ptr` to improve read performance in loop.
-    // This calculation is based on byte pointer, and not the scaled addition.
-    ptr = {
-        let align_offset = VECTOR_SIZE - ((start as usize) & VECTOR_ALIGN);
-        (start as *const u8).add(align_offset) as *const i32
-    };
-
-    // The pointer will advance at least one element and at most by the
-    // number of elements in one vector.
-    debug_assert!(start < ptr);
-    debug_assert!(ptr.offset_from(start) as usize <= VECTOR_ELEMENTS);
+    vector::wmemchr::<i32, __m256i>(needle, haystack, len, Some(HUGE_SIZE_I32))
+}
 
-    // 128 byte (32 element) loop.
-    if let Some(loop_end) = (end as usize).checked_sub(LOOP_SIZE) {
-        while (ptr as usize) <= loop_end {
-            debug_assert_eq!((ptr as usize) % VECTOR_SIZE, 0);
-
-            let p = ptr as *const __m256i;
-
-            // Load 4 vectors of characters.
-            let a = _mm256_load_si256(p);
-            let b = _mm256_load_si256(p.add(1));
-            let c = _mm256_load_si256(p.add(2));
-            let d = _mm256_load_si256(p.add(3));
-
-            // Look for needle in vectors.
-            let eq_a = _mm256_cmpeq_epi32(a, v_needle);
-            let eq_b = _mm256_cmpeq_epi32(b, v_needle);
-            let eq_c = _mm256_cmpeq_epi32(c, v_needle);
-            let eq_d = _mm256_cmpeq_epi32(d, v_needle);
-
-            // Determine if either vector contained the needle.
-            let or_ab = _mm256_or_si256(eq_a, eq_b);
-            let or_cd = _mm256_or_si256(eq_c, eq_d);
-            let or = _mm256_or_si256(or_ab, or_cd);
-
-            // If any vector contains the needle, we will search for it in each vector.
-            if _mm256_movemask_epi8(or) != 0 {
-                // Keep track of the offset from the start of the haystack.
-                let mut offset = ptr.offset_from(start) as usize;
-
-                let mask = _mm256_movemask_epi8(eq_a);
-                if let Some(mask) = NonZeroI32::new(mask) {
-                    return Some(offset + forward_pos(mask));
-                }
-                offset += VECTOR_ELEMENTS;
-
-                let mask = _mm256_movemask_epi8(eq_b);
-                if let Some(mask) = NonZeroI32::new(mask) {
-                    return Some(offset + forward_pos(mask));
-                }
-                offset += VECTOR_ELEMENTS;
-
-                let mask = _mm256_movemask_epi8(eq_c);
-                if let Some(mask) = NonZeroI32::new(mask) {
-                    return Some(offset + forward_pos(mask));
-                }
-                offset += VECTOR_ELEMENTS;
-
-                let mask = _mm256_movemask_epi8(eq_d);
-                debug_assert_ne!(mask, 0);
-                let mask = NonZeroI32::new_unchecked(mask);
-                return Some(offset + forward_pos(mask));
-            }
-
-            ptr = ptr.add(LOOP_ELEMENTS);
-        }
+/// Like [`wmemchr`], except that `haystack` need only be aligned to a byte
+/// boundary, rather than to the size of an element.
+#[cfg_attr(target_feature = "avx2", inline)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn wmemchr_unaligned(needle: i32, haystack: *const i32, len: usize) -> Option<usize> {
+    // If haystack is shorter than two vectors, then search it with two
+    // overlapping loads.
+    if len < VECTOR_ELEMENTS {
+        return wmemchr_tiny(needle, haystack, len);
     }
-
-    // 32 byte (8 element) loop.
-    if let Some(loop_end) = (end as usize).checked_sub(VECTOR_SIZE) {
-        while (ptr as usize) <= loop_end {
-            debug_assert_eq!((ptr as usize) % VECTOR_SIZE, 0);
-
-            let chunk = _mm256_load_si256(ptr as *const __m256i);
-            let eq = _mm256_cmpeq_epi32(chunk, v_needle);
-
-            let mask = _mm256_movemask_epi8(eq);
-            if let Some(mask) = NonZeroI32::new(mask) {
-                let offset = ptr.offset_from(start) as usize;
-                return Some(offset + forward_pos(mask));
-            }
-
-            ptr = ptr.add(VECTOR_ELEMENTS);
-        }
+    if len < 2 * VECTOR_ELEMENTS {
+        return vector::wmemchr_pair::<i32, __m256i>(needle, haystack, len);
     }
 
-    // Invariant: `0 <= end - ptr < VECTOR_SIZE`.
-
-    // We can search the remaining elements by shifting `ptr` back and doing an
-    // unaligned forward search.
-
-    if ptr < end {
-        let remaining = end.offset_from(ptr) as usize;
+    vector::wmemchr_unaligned::<i32, __m256i>(needle, haystack, len)
+}
 
-        debug_assert!(remaining < VECTOR_ELEMENTS);
-        ptr = ptr.sub(VECTOR_ELEMENTS - remaining);
-        debug_assert_eq!(end.offset_from(ptr) as usize, VECTOR_ELEMENTS);
+/// Searches a haystack for the last occurrence of `needle`.
+#[cfg_attr(target_feature = "avx2", inline)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn wmemrchr(needle: i32, haystack: *const i32, len: usize) -> Option<usize> {
+    vector::wmemrchr::<i32, __m256i>(needle, haystack, len)
+}
 
-        return forward_search_unaligned(start, end, ptr, v_needle);
-    }
+/// Counts the occurrences of `needle` in a haystack.
+#[cfg_attr(target_feature = "avx2", inline)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn wmemcount(needle: i32, haystack: *const i32, len: usize) -> usize {
+    vector::wmemcount::<i32, __m256i>(needle, haystack, len)
+}
 
-    None
+/// Returns the number of elements before the null terminator of a string, or
+/// `maxlen` if there is none within it, with aligned loads.
+#[cfg_attr(target_feature = "avx2", inline)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn wcsnlen(s: *const i32, maxlen: usize) -> usize {
+    vector::wcsnlen::<i32, __m256i>(s, maxlen)
 }
 
-#[inline]
+/// Returns the index of the first occurrence of `needle` in a string, or of
+/// its null terminator, with aligned loads.
+#[cfg_attr(target_feature = "avx2", inline)]
 #[target_feature(enable = "avx2")]
-unsafe fn forward_search_unaligned(
-    start: *const i32,
-    end: *const i32,
-    ptr: *const i32,
-    v_needle: __m256i,
-) -> Option<usize> {
-    debug_assert!(start <= ptr);
-    debug_assert!(end.offset_from(ptr) as usize >= VECTOR_ELEMENTS);
-
-    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
-    let eq = _mm256_cmpeq_epi32(chunk, v_needle);
-
-    let mask = _mm256_movemask_epi8(eq);
-    if let Some(mask) = NonZeroI32::new(mask) {
-        let offset = ptr.offset_from(start) as usize;
-        Some(offset + forward_pos(mask))
-    } else {
-        None
-    }
+pub unsafe fn wcschr(s: *const i32, needle: i32) -> usize {
+    vector::wcschr::<i32, __m256i>(s, needle)
 }
 
-#[inline]
 #[target_feature(enable = "avx2")]
-unsafe fn wmemchr_small(
-    needle: i32,
-    start: *const i32,
-    end: *const i32,
-    len: usize,
-) -> Option<usize> {
+pub unsafe fn swap_endianness(data: *mut i32, len: usize) {
+    let start = data;
+    let end = data.add(len);
     let mut ptr = start;
 
-    // If haystack length is less than the number of elements in a smaller
-    // packed vector, then just fallback to by element search.
-    if len < SMALL_VECTOR_ELEMENTS {
-        while ptr < end {
-            if *ptr == needle {
-                return Some(ptr.offset_from(start) as usize);
-            }
-            ptr = ptr.add(1);
-        }
-        return None;
-    }
-
-    debug_assert!(end.offset_from(start) as usize >= SMALL_VECTOR_ELEMENTS);
-
-    // Broadcast the needle across the elements of the vector.
-    let v_needle = _mm_set1_epi32(needle);
-
-    // Search the first small vector
-    if let Some(pos) = forward_search_unaligned_small(start, end, ptr, v_needle) {
-        return Some(pos);
-    }
-    ptr = ptr.add(SMALL_VECTOR_ELEMENTS);
-
-    // Invariant: `0 <= end - ptr < SMALL_VECTOR_SIZE`.
+    debug_assert!(start <= end);
 
-    // We can search the remaining elements by shifting `ptr` back and doing an
-    // unaligned forward search.
+    // Shuffle the bytes within each 32-bit lane, the shuffle is performed
+    // independently on each 128-bit half of the vector.
+    let shuffle = _mm256_broadcastsi128_si256(_mm_setr_epi8(
+        3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12,
+    ));
 
-    if ptr < end {
-        let remaining = len - SMALL_VECTOR_ELEMENTS;
+    // 32 byte (8 element) loop.
+    if let Some(loop_end) = (end as usize).checked_sub(VECTOR_SIZE) {
+        while (ptr as usize) <= loop_end {
+            let p = ptr as *mut __m256i;
 
-        debug_assert!(remaining < SMALL_VECTOR_ELEMENTS);
-        ptr = ptr.sub(SMALL_VECTOR_ELEMENTS - remaining);
-        debug_assert_eq!(end.offset_from(ptr) as usize, SMALL_VECTOR_ELEMENTS);
+            let chunk = _mm256_loadu_si256(p);
+            _mm256_storeu_si256(p, _mm256_shuffle_epi8(chunk, shuffle));
 
-        return forward_search_unaligned_small(start, end, ptr, v_needle);
+            ptr = ptr.add(VECTOR_ELEMENTS);
+        }
     }
 
-    None
-}
-
-#[inline]
-#[target_feature(enable = "avx2")]
-unsafe fn forward_search_unaligned_small(
-    start: *const i32,
-    end: *const i32,
-    ptr: *const i32,
-    v_needle: __m128i,
-) -> Option<usize> {
-    debug_assert!(start <= ptr);
-    debug_assert!(end.offset_from(ptr) as usize >= SMALL_VECTOR_ELEMENTS);
-
-    let chunk = _mm_loadu_si128(ptr as *const __m128i);
-    let eq = _mm_cmpeq_epi32(chunk, v_needle);
-
-    let mask = _mm_movemask_epi8(eq);
-    if let Some(mask) = NonZeroI32::new(mask) {
-        let offset = ptr.offset_from(start) as usize;
-        Some(offset + forward_pos(mask))
-    } else {
-        None
+    // Unlike searching, the remaining elements cannot be handled by shifting
+    // `ptr` back, since elements would be swapped twice.
+    while ptr < end {
+        *ptr = (*ptr).swap_bytes();
+        ptr = ptr.add(1);
     }
 }
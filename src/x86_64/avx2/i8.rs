@@ -0,0 +1,42 @@
+use core::mem;
+
+use crate::vector;
+use crate::x86_64::arch::*;
+use crate::x86_64::HUGE_SIZE_I8;
+use crate::x86_64::sse2::i8::wmemchr_tiny;
+
+const VECTOR_SIZE: usize = mem::size_of::<__m256i>();
+
+const VECTOR_ELEMENTS: usize = VECTOR_SIZE / mem::size_of::<i8>();
+
+/// Searches a haystack by its size class: tiny haystacks, shorter than two
+/// vectors, are searched with two overlapping loads, while huge haystacks, of
+/// at least [`HUGE_SIZE_I8`] bytes, start with a loop unrolled over 8 vectors.
+#[cfg_attr(target_feature = "avx2", inline)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn wmemchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize> {
+    // If haystack is shorter than two vectors, then search it with two
+    // overlapping loads.
+    if len < VECTOR_ELEMENTS {
+        return wmemchr_tiny(needle, haystack, len);
+    }
+    if len < 2 * VECTOR_ELEMENTS {
+        return vector::wmemchr_pair::<i8, __m256i>(needle, haystack, len);
+    }
+
+    vector::wmemchr::<i8, __m256i>(needle, haystack, len, Some(HUGE_SIZE_I8))
+}
+
+/// Searches a haystack for the last occurrence of `needle`.
+#[cfg_attr(target_feature = "avx2", inline)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn wmemrchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize> {
+    vector::wmemrchr::<i8, __m256i>(needle, haystack, len)
+}
+
+/// Counts the occurrences of `needle` in a haystack.
+#[cfg_attr(target_feature = "avx2", inline)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn wmemcount(needle: i8, haystack: *const i8, len: usize) -> usize {
+    vector::wmemcount::<i8, __m256i>(needle, haystack, len)
+}
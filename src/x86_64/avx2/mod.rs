@@ -1,2 +1,56 @@
+use core::mem;
+
+use crate::vector::Vector;
+use crate::x86_64::arch::*;
+
 pub mod i16;
 pub mod i32;
+pub mod i8;
+
+macro_rules! impl_vector {
+    ($ty:ident, $set1:ident, $cmpeq:ident) => {
+        unsafe impl Vector<$ty> for __m256i {
+            const LANES: usize = mem::size_of::<__m256i>() / mem::size_of::<$ty>();
+            // There is no `_mm256_movemask_epi16`, so each element sets a bit for
+            // each of its bytes.
+            const MASK_STRIDE: usize = mem::size_of::<$ty>();
+            const MASK_BITS: u32 = mem::size_of::<$ty>() as u32;
+
+            type Mask = __m256i;
+
+            #[inline(always)]
+            unsafe fn splat(value: $ty) -> __m256i {
+                $set1(value)
+            }
+
+            #[inline(always)]
+            unsafe fn load_aligned(ptr: *const $ty) -> __m256i {
+                _mm256_load_si256(ptr as *const __m256i)
+            }
+
+            #[inline(always)]
+            unsafe fn load_unaligned(ptr: *const $ty) -> __m256i {
+                _mm256_loadu_si256(ptr as *const __m256i)
+            }
+
+            #[inline(always)]
+            unsafe fn cmpeq(self, other: __m256i) -> __m256i {
+                $cmpeq(self, other)
+            }
+
+            #[inline(always)]
+            unsafe fn or(a: __m256i, b: __m256i) -> __m256i {
+                _mm256_or_si256(a, b)
+            }
+
+            #[inline(always)]
+            unsafe fn movemask(mask: __m256i) -> u64 {
+                u64::from(_mm256_movemask_epi8(mask) as u32)
+            }
+        }
+    };
+}
+
+impl_vector!(i8, _mm256_set1_epi8, _mm256_cmpeq_epi8);
+impl_vector!(i16, _mm256_set1_epi16, _mm256_cmpeq_epi16);
+impl_vector!(i32, _mm256_set1_epi32, _mm256_cmpeq_epi32);
@@ -0,0 +1,91 @@
+//! Runtime CPU feature detection for `no_std` builds.
+//!
+//! This queries the processor directly with `cpuid`, and checks that the OS
+//! saves the extended register state with `xgetbv`, since the wider registers
+//! cannot be used otherwise.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::x86_64::arch::{__cpuid, __cpuid_count, _xgetbv};
+
+/// Set once the features have been detected.
+const INITIALIZED: u32 = 1 << 0;
+
+pub const SSE2: u32 = 1 << 1;
+pub const SSSE3: u32 = 1 << 2;
+pub const SSE42: u32 = 1 << 3;
+pub const AVX2: u32 = 1 << 4;
+pub const AVX512VL: u32 = 1 << 5;
+pub const AVX512BW: u32 = 1 << 6;
+
+/// Bits of `XCR0` for the state of the XMM and YMM registers.
+const XCR0_YMM: u64 = 0b0000_0110;
+/// Bits of `XCR0` for the state of the XMM, YMM, opmask and ZMM registers.
+const XCR0_ZMM: u64 = 0b1110_0110;
+
+static FEATURES: AtomicU32 = AtomicU32::new(0);
+
+/// Returns `true` if the CPU and OS support all of the given features.
+#[inline]
+pub fn is_detected(features: u32) -> bool {
+    let mut detected = FEATURES.load(Ordering::Relaxed);
+    if detected & INITIALIZED == 0 {
+        detected = detect() | INITIALIZED;
+        FEATURES.store(detected, Ordering::Relaxed);
+    }
+    detected & features == features
+}
+
+#[cold]
+fn detect() -> u32 {
+    let mut features = 0;
+
+    // SAFETY: `cpuid` is available on all x86_64 processors, and on all x86
+    //         processors targeted by Rust (i586 and later). Leaf 7 is only
+    //         queried if it is supported. `xgetbv` is only executed if the OS
+    //         has enabled it, as indicated by the OSXSAVE bit.
+    unsafe {
+        let max_leaf = __cpuid(0).eax;
+        let leaf1 = __cpuid(1);
+
+        if leaf1.edx & (1 << 26) != 0 {
+            features |= SSE2;
+        }
+        if leaf1.ecx & (1 << 9) != 0 {
+            features |= SSSE3;
+        }
+        if leaf1.ecx & (1 << 20) != 0 {
+            features |= SSE42;
+        }
+
+        let osxsave = leaf1.ecx & (1 << 27) != 0;
+        let avx = leaf1.ecx & (1 << 28) != 0;
+
+        if osxsave && avx && max_leaf >= 7 {
+            let xcr0 = xgetbv();
+            let leaf7 = __cpuid_count(7, 0);
+
+            if xcr0 & XCR0_YMM == XCR0_YMM && leaf7.ebx & (1 << 5) != 0 {
+                features |= AVX2;
+            }
+
+            // AVX-512 extensions also require the AVX-512 foundation.
+            if xcr0 & XCR0_ZMM == XCR0_ZMM && leaf7.ebx & (1 << 16) != 0 {
+                if leaf7.ebx & (1 << 30) != 0 {
+                    features |= AVX512BW;
+                }
+                if leaf7.ebx & (1 << 31) != 0 {
+                    features |= AVX512VL;
+                }
+            }
+        }
+    }
+
+    features
+}
+
+#[inline]
+#[target_feature(enable = "xsave")]
+unsafe fn xgetbv() -> u64 {
+    _xgetbv(0)
+}
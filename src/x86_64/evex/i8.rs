@@ -0,0 +1,41 @@
+use core::mem;
+
+use crate::vector;
+use crate::x86_64::arch::*;
+use crate::x86_64::evex::Masked256;
+use crate::x86_64::HUGE_SIZE_I8;
+use crate::x86_64::sse2::i8::wmemchr_tiny;
+
+const VECTOR_SIZE: usize = mem::size_of::<__m256i>();
+
+const VECTOR_ELEMENTS: usize = VECTOR_SIZE / mem::size_of::<i8>();
+
+/// Searches a haystack by its size class, as for the AVX2 kernel.
+#[cfg_attr(all(target_feature = "avx512vl", target_feature = "avx512bw"), inline)]
+#[target_feature(enable = "avx512vl,avx512bw")]
+pub unsafe fn wmemchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize> {
+    // If haystack is shorter than two vectors, then search it with two
+    // overlapping loads.
+    if len < VECTOR_ELEMENTS {
+        return wmemchr_tiny(needle, haystack, len);
+    }
+    if len < 2 * VECTOR_ELEMENTS {
+        return vector::wmemchr_pair::<i8, Masked256>(needle, haystack, len);
+    }
+
+    vector::wmemchr::<i8, Masked256>(needle, haystack, len, Some(HUGE_SIZE_I8))
+}
+
+/// Searches a haystack for the last occurrence of `needle`.
+#[cfg_attr(all(target_feature = "avx512vl", target_feature = "avx512bw"), inline)]
+#[target_feature(enable = "avx512vl,avx512bw")]
+pub unsafe fn wmemrchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize> {
+    vector::wmemrchr::<i8, Masked256>(needle, haystack, len)
+}
+
+/// Counts the occurrences of `needle` in a haystack.
+#[cfg_attr(all(target_feature = "avx512vl", target_feature = "avx512bw"), inline)]
+#[target_feature(enable = "avx512vl,avx512bw")]
+pub unsafe fn wmemcount(needle: i8, haystack: *const i8, len: usize) -> usize {
+    vector::wmemcount::<i8, Masked256>(needle, haystack, len)
+}
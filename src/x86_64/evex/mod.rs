@@ -1,2 +1,61 @@
+use core::mem;
+
+use crate::vector::Vector;
+use crate::x86_64::arch::*;
+
 pub mod i16;
 pub mod i32;
+pub mod i8;
+
+/// A 256-bit vector, compared into an AVX-512 mask register rather than into
+/// another vector.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Masked256(__m256i);
+
+macro_rules! impl_vector {
+    ($ty:ident, $mask:ty, $set1:ident, $cmpeq:ident) => {
+        unsafe impl Vector<$ty> for Masked256 {
+            const LANES: usize = mem::size_of::<__m256i>() / mem::size_of::<$ty>();
+            // Mask registers hold a single bit for each element.
+            const MASK_STRIDE: usize = 1;
+            const MASK_BITS: u32 = 1;
+
+            type Mask = $mask;
+
+            #[inline(always)]
+            unsafe fn splat(value: $ty) -> Masked256 {
+                Masked256($set1(value))
+            }
+
+            #[inline(always)]
+            unsafe fn load_aligned(ptr: *const $ty) -> Masked256 {
+                Masked256(_mm256_load_si256(ptr as *const __m256i))
+            }
+
+            #[inline(always)]
+            unsafe fn load_unaligned(ptr: *const $ty) -> Masked256 {
+                Masked256(_mm256_loadu_si256(ptr as *const __m256i))
+            }
+
+            #[inline(always)]
+            unsafe fn cmpeq(self, other: Masked256) -> $mask {
+                $cmpeq(self.0, other.0)
+            }
+
+            #[inline(always)]
+            unsafe fn or(a: $mask, b: $mask) -> $mask {
+                a | b
+            }
+
+            #[inline(always)]
+            unsafe fn movemask(mask: $mask) -> u64 {
+                u64::from(mask)
+            }
+        }
+    };
+}
+
+impl_vector!(i8, u32, _mm256_set1_epi8, _mm256_cmpeq_epi8_mask);
+impl_vector!(i16, u16, _mm256_set1_epi16, _mm256_cmpeq_epi16_mask);
+impl_vector!(i32, u8, _mm256_set1_epi32, _mm256_cmpeq_epi32_mask);
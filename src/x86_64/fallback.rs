@@ -0,0 +1,51 @@
+//! Pointer based wrappers around the platform independent kernels, so that
+//! they can be dispatched to in the same way as the x86_64 kernels.
+
+macro_rules! fallback {
+    ($($ty:ident),*) => {
+        $(
+            pub mod $ty {
+                use crate::char::KernelFn;
+                use crate::fallback::Kernel;
+
+                pub unsafe fn wmemchr(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    Kernel::kernel(needle, haystack, len)
+                }
+
+                pub unsafe fn wmemrchr(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    Kernel::kernel_reverse(needle, haystack, len)
+                }
+
+                pub unsafe fn wmemcount(needle: $ty, haystack: *const $ty, len: usize) -> usize {
+                    Kernel::kernel_count(needle, haystack, len)
+                }
+
+                fallback!(@wide $ty);
+            }
+        )*
+    };
+    (@wide i8) => {};
+    (@wide $ty:ident) => {
+        pub unsafe fn wmemchr_unaligned(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+            if (haystack as usize) % core::mem::align_of::<$ty>() == 0 {
+                wmemchr(needle, haystack, len)
+            } else {
+                let bytes = core::slice::from_raw_parts(
+                    haystack as *const u8,
+                    len * core::mem::size_of::<$ty>(),
+                );
+                crate::const_fn::$ty::const_wmemchr_ne_bytes(needle, bytes)
+            }
+        }
+
+        pub unsafe fn wcsnlen(s: *const $ty, maxlen: usize) -> usize {
+            crate::fallback::wcsnlen(s, maxlen)
+        }
+
+        pub unsafe fn wcschr(s: *const $ty, needle: $ty) -> usize {
+            crate::fallback::wcschr(s, needle)
+        }
+    };
+}
+
+fallback!(i8, i16, i32);
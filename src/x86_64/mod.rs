@@ -1,11 +1,114 @@
-//! Optimised implementation for x86_64 platforms.
+//! Optimised implementation for x86 and x86_64 platforms.
+//!
+//! On x86 platforms, this module is named `x86`.
 
-use crate::char::{KernelFn, Wide};
+#[cfg(target_arch = "x86")]
+use core::arch::x86 as arch;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64 as arch;
+use core::sync::atomic::{AtomicU8, Ordering};
 
+use crate::backend::Backend;
+use crate::char::{as_repr_slice, to_repr, KernelFn, SwapBytes, Wide, WideRepr};
+use crate::naive;
+
+// Kernels of the backends that are not statically dispatched to are unused.
+#[cfg_attr(
+    all(
+        feature = "unstable",
+        target_feature = "avx512vl",
+        target_feature = "avx512bw",
+    ),
+    allow(dead_code)
+)]
 mod avx2;
+#[cfg(not(feature = "std"))]
+mod cpuid;
 #[cfg(feature = "unstable")]
 mod evex;
+#[cfg_attr(target_feature = "avx2", allow(dead_code))]
+mod fallback;
+#[cfg_attr(target_feature = "avx2", allow(dead_code))]
 mod sse2;
+mod sse42;
+mod ssse3;
+
+/// The haystack size, in bytes, from which the AVX2 and EVEX kernels search
+/// for the first occurrence of an 8-bit element with a loop unrolled over 8
+/// vectors, rather than 4.
+///
+/// This is measured by the `huge` benchmarks in the `size` module of the bench
+/// crate.
+const HUGE_SIZE_I8: usize = 1024;
+
+/// The haystack size, in bytes, from which the AVX2 and EVEX kernels search
+/// for the first occurrence of a 16-bit element with a loop unrolled over 8
+/// vectors, rather than 4.
+///
+/// This is measured by the `huge` benchmarks in the `size` module of the bench
+/// crate.
+const HUGE_SIZE_I16: usize = 768;
+
+/// The haystack size, in bytes, from which the AVX2 and EVEX kernels search
+/// for the first occurrence of a 32-bit element with a loop unrolled over 8
+/// vectors, rather than 4.
+///
+/// This is measured by the `huge` benchmarks in the `size` module of the bench
+/// crate.
+const HUGE_SIZE_I32: usize = 1024;
+
+/// Searches with the loops of the AVX2 and EVEX kernels, with the loop
+/// unrolled over 8 vectors used for every haystack or for none, to measure
+/// the thresholds of huge haystacks in the `huge` benchmarks.
+///
+/// This is only available with the `bench` feature, and is not part of the
+/// public API.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod huge {
+    use crate::vector;
+    use crate::x86_64::arch::*;
+    #[cfg(feature = "unstable")]
+    use crate::x86_64::evex::Masked256;
+
+    macro_rules! huge {
+        ($($(#[$attr:meta])* $feature:literal, $name:ident: $ty:ty => $ity:ty, $vector:ty;)*) => {
+            $(
+                /// Searches a haystack of at least one 256-bit vector for the
+                /// first occurrence of `needle`, starting with the loop
+                /// unrolled over 8 vectors if `wide`.
+                ///
+                /// # Safety
+                ///
+                /// The CPU must support the target features of the kernel.
+                $(#[$attr])*
+                #[target_feature(enable = $feature)]
+                pub unsafe fn $name(needle: $ty, haystack: &[$ty], wide: bool) -> Option<usize> {
+                    assert!(haystack.len() * core::mem::size_of::<$ty>() >= 32);
+
+                    vector::wmemchr::<$ity, $vector>(
+                        needle as $ity,
+                        haystack.as_ptr() as *const $ity,
+                        haystack.len(),
+                        if wide { Some(0) } else { None },
+                    )
+                }
+            )*
+        };
+    }
+
+    huge! {
+        "avx2", avx2_u8: u8 => i8, __m256i;
+        "avx2", avx2_u16: u16 => i16, __m256i;
+        "avx2", avx2_u32: u32 => i32, __m256i;
+        #[cfg(feature = "unstable")]
+        "avx512vl,avx512bw", evex_u8: u8 => i8, Masked256;
+        #[cfg(feature = "unstable")]
+        "avx512vl,avx512bw", evex_u16: u16 => i16, Masked256;
+        #[cfg(feature = "unstable")]
+        "avx512vl,avx512bw", evex_u32: u32 => i32, Masked256;
+    }
+}
 
 /// Returns the index of the first occurrence of a wide character in a slice,
 /// or [`None`] if the character is not found.
@@ -19,6 +122,9 @@ mod sse2;
 /// # #[cfg(not(miri))]
 /// # {
 /// use wchar::wch;
+/// # #[cfg(target_arch = "x86")]
+/// # use wmemchr::x86::wmemchr;
+/// # #[cfg(target_arch = "x86_64")]
 /// use wmemchr::x86_64::wmemchr;
 ///
 /// let haystack = wch!(u16, "foo bar");
@@ -29,115 +135,691 @@ mod sse2;
 /// # }
 /// ```
 #[inline(always)]
-pub fn wmemchr<T: Wide>(needle: T, haystack: &[T]) -> Option<usize> {
-    T::wmemchr_x86_64(needle, haystack)
+pub fn wmemchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    // SAFETY: A slice is valid for reads of its length.
+    let pos = unsafe { T::Repr::wmemchr_x86_64(needle, haystack.as_ptr(), haystack.len()) };
+
+    #[cfg(feature = "checked")]
+    crate::checked::check::<T::Repr, _, _, _>(
+        concat!(module_path!(), "::wmemchr"),
+        false,
+        needle,
+        haystack,
+        pos,
+        naive::wmemchr(needle, haystack),
+    );
+
+    pos
+}
+
+/// Returns the index of the first occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// This searches a haystack of opposite endianness in place, by swapping the
+/// byte order of the needle.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// # fn main() {
+/// # #[cfg(not(miri))]
+/// # {
+/// # #[cfg(target_arch = "x86")]
+/// # use wmemchr::x86::wmemchr_swapped;
+/// # #[cfg(target_arch = "x86_64")]
+/// use wmemchr::x86_64::wmemchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemchr_swapped(0x006f, &haystack), Some(1));
+/// assert_eq!(wmemchr_swapped(0x007a, &haystack), None);
+/// # }
+/// # }
+/// ```
+#[inline(always)]
+pub fn wmemchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the index of the last occurrence of a wide character in a slice,
+/// or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// # fn main() {
+/// # #[cfg(not(miri))]
+/// # {
+/// use wchar::wch;
+/// # #[cfg(target_arch = "x86")]
+/// # use wmemchr::x86::wmemrchr;
+/// # #[cfg(target_arch = "x86_64")]
+/// use wmemchr::x86_64::wmemrchr;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemrchr(wch!(u16, 'o'), haystack), Some(2));
+/// assert_eq!(wmemrchr(wch!(u16, 'z'), haystack), None);
+/// # }
+/// # }
+/// ```
+#[inline(always)]
+pub fn wmemrchr<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize> {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    // SAFETY: A slice is valid for reads of its length.
+    let pos = unsafe { T::Repr::wmemrchr_x86_64(needle, haystack.as_ptr(), haystack.len()) };
+
+    #[cfg(feature = "checked")]
+    crate::checked::check::<T::Repr, _, _, _>(
+        concat!(module_path!(), "::wmemrchr"),
+        false,
+        needle,
+        haystack,
+        pos,
+        naive::wmemrchr(needle, haystack),
+    );
+
+    pos
+}
+
+/// Returns the number of occurrences of a wide character in a slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// # fn main() {
+/// # #[cfg(not(miri))]
+/// # {
+/// use wchar::wch;
+/// # #[cfg(target_arch = "x86")]
+/// # use wmemchr::x86::wmemcount;
+/// # #[cfg(target_arch = "x86_64")]
+/// use wmemchr::x86_64::wmemcount;
+///
+/// let haystack = wch!(u16, "foo bar");
+///
+/// assert_eq!(wmemcount(wch!(u16, 'o'), haystack), 2);
+/// assert_eq!(wmemcount(wch!(u16, 'z'), haystack), 0);
+/// # }
+/// # }
+/// ```
+#[inline(always)]
+pub fn wmemcount<T: WideRepr>(needle: T, haystack: &[T]) -> usize {
+    let (needle, haystack) = (to_repr(needle), as_repr_slice(haystack));
+    // SAFETY: A slice is valid for reads of its length.
+    let count = unsafe { T::Repr::wmemcount_x86_64(needle, haystack.as_ptr(), haystack.len()) };
+
+    #[cfg(feature = "checked")]
+    crate::checked::check::<T::Repr, _, _, _>(
+        concat!(module_path!(), "::wmemcount"),
+        false,
+        needle,
+        haystack,
+        count,
+        naive::wmemcount(needle, haystack),
+    );
+
+    count
+}
+
+/// Returns the index of the last occurrence of a byte swapped wide character
+/// in a slice, or [`None`] if the character is not found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// # fn main() {
+/// # #[cfg(not(miri))]
+/// # {
+/// # #[cfg(target_arch = "x86")]
+/// # use wmemchr::x86::wmemrchr_swapped;
+/// # #[cfg(target_arch = "x86_64")]
+/// use wmemchr::x86_64::wmemrchr_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemrchr_swapped(0x006f, &haystack), Some(2));
+/// assert_eq!(wmemrchr_swapped(0x007a, &haystack), None);
+/// # }
+/// # }
+/// ```
+#[inline(always)]
+pub fn wmemrchr_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> Option<usize>
+where
+    T::Repr: SwapBytes,
+{
+    wmemrchr(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
+}
+
+/// Returns the number of occurrences of a byte swapped wide character in a
+/// slice.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// # fn main() {
+/// # #[cfg(not(miri))]
+/// # {
+/// # #[cfg(target_arch = "x86")]
+/// # use wmemchr::x86::wmemcount_swapped;
+/// # #[cfg(target_arch = "x86_64")]
+/// use wmemchr::x86_64::wmemcount_swapped;
+///
+/// let haystack = [0x6600u16, 0x6f00, 0x6f00];
+///
+/// assert_eq!(wmemcount_swapped(0x006f, &haystack), 2);
+/// assert_eq!(wmemcount_swapped(0x007a, &haystack), 0);
+/// # }
+/// # }
+/// ```
+#[inline(always)]
+pub fn wmemcount_swapped<T: WideRepr>(needle: T, haystack: &[T]) -> usize
+where
+    T::Repr: SwapBytes,
+{
+    wmemcount(to_repr(needle).swap_bytes(), as_repr_slice(haystack))
 }
 
 macro_rules! unsafe_ifuncs {
     ($(
-        fn $ty:ident::$name:ident($($arg:ident: $arg_ty:ty),* $(,)?) $(-> $ret_ty:ty)?;
+        mod $ty:ident {
+            $(
+                fn $name:ident($($arg:ident: $arg_ty:ty),* $(,)?) $(-> $ret_ty:ty)?;
+            )*
+        }
     )*) => {
         $(
-            unsafe_ifuncs! {
-                @__item
-                [$ty]
-                [$name]
-                [$($arg: $arg_ty),*]
-                [$($ret_ty)?]
-                [fn($($arg_ty),*) $(-> $ret_ty)?]
+            mod $ty {
+                use core::sync::atomic::{AtomicU8, Ordering};
+
+                use crate::backend::Backend;
+
+                /// The backend that the kernels were last selected from.
+                static SELECTED: AtomicU8 = AtomicU8::new(super::UNINIT);
+
+                /// Returns the backend of the kernels selected for this element
+                /// type, selecting them if needed.
+                pub fn backend() -> Backend {
+                    if let Some(backend) = super::STATIC_BACKEND {
+                        return backend;
+                    }
+
+                    if SELECTED.load(Ordering::Relaxed) == super::UNINIT {
+                        $(
+                            $name::init();
+                        )*
+                    }
+
+                    // Only valid backends are ever stored, other than `UNINIT`.
+                    Backend::from_u8(SELECTED.load(Ordering::Relaxed)).unwrap()
+                }
+
+                $(
+                    unsafe_ifuncs! {
+                        @__item
+                        [$ty]
+                        [$name]
+                        [$($arg: $arg_ty),*]
+                        [$($ret_ty)?]
+                        [fn($($arg_ty),*) $(-> $ret_ty)?]
+                    }
+                )*
             }
         )*
+
+        /// Selects the kernels for every dispatched function.
+        fn init_ifuncs() {
+            $($(
+                $ty::$name::init();
+            )*)*
+        }
     };
     (@__item [$ty:ident] [$name:ident] [$($arg:ident: $arg_ty:ty),*] [$($ret_ty:ty)?] [$fn_ty:ty]) => {
-        mod $ty {
-            use core::mem;
-            use core::sync::atomic::{AtomicPtr, Ordering};
-
-            type FnRaw = *mut ();
-
-            static FN: AtomicPtr<()> = AtomicPtr::new(detect as FnRaw);
-
-            fn detect($($arg: $arg_ty),*) $(-> $ret_ty)? {
-                #[inline(always)]
-                fn select() -> FnRaw {
-                    if is_x86_feature_detected!("avx2") {
-                        #[cfg(feature = "unstable")]
-                        {
-                            if is_x86_feature_detected!("avx512vl") && is_x86_feature_detected!("avx512bw") {
-                                return super::evex::$ty::$name as FnRaw;
-                            }
+        pub use self::$name::$name;
+
+        pub(super) mod $name {
+            cfg_if::cfg_if! {
+                if #[cfg(all(
+                    feature = "unstable",
+                    target_feature = "avx512vl",
+                    target_feature = "avx512bw",
+                ))] {
+                    // The target guarantees support for the kernel, so it is
+                    // called directly, allowing it to be inlined.
+                    pub use super::super::evex::$ty::$name;
+
+                    pub fn init() {}
+                } else if #[cfg(target_feature = "avx2")] {
+                    // The target guarantees support for the kernel, so it is
+                    // called directly, allowing it to be inlined.
+                    pub use super::super::avx2::$ty::$name;
+
+                    pub fn init() {}
+                } else {
+                    use core::mem;
+                    use core::sync::atomic::{AtomicPtr, Ordering};
+
+                    use crate::backend::Backend;
+
+                    type FnRaw = *mut ();
+
+                    static FN: AtomicPtr<()> = AtomicPtr::new(detect as FnRaw);
+
+                    /// Returns the kernel for a backend.
+                    #[inline(always)]
+                    fn kernel(backend: Backend) -> FnRaw {
+                        match backend {
+                            #[cfg(feature = "unstable")]
+                            Backend::Evex => super::super::evex::$ty::$name as FnRaw,
+                            #[cfg(not(feature = "unstable"))]
+                            Backend::Evex => unreachable!("the evex backend is not supported"),
+                            Backend::Avx2 => super::super::avx2::$ty::$name as FnRaw,
+                            Backend::Sse2 => super::super::sse2::$ty::$name as FnRaw,
+                            Backend::Fallback => super::super::fallback::$ty::$name as FnRaw,
+                            Backend::Portable => unreachable!("the portable backend is not supported"),
                         }
-                        super::avx2::$ty::$name as FnRaw
-                    } else {
-                        // SSE2 is supported for all for x86_64 processors.
-                        super::sse2::$ty::$name as FnRaw
                     }
-                }
-                let f = select();
 
-                FN.store(f, Ordering::Relaxed);
+                    /// Selects the kernel for the current backend, recording
+                    /// the backend as selected for the element type.
+                    pub fn init() {
+                        let backend = super::super::backend();
+                        super::SELECTED.store(backend as u8, Ordering::Relaxed);
+                        FN.store(kernel(backend), Ordering::Relaxed);
+                    }
+
+                    fn detect($($arg: $arg_ty),*) $(-> $ret_ty)? {
+                        let backend = super::super::backend();
+                        let f = kernel(backend);
 
-                // SAFETY: By virtue of the caller contract, $fn_ty is a function
-                //         pointer, which is always safe to transmute with `*mut ()`.
-                //         Also, if `f` is the AVX2 routine, then it is guaranteed to be
-                //         supported since we checked the `avx2` feature.
-                unsafe {
-                    (mem::transmute::<FnRaw, $fn_ty>(f))($($arg),*)
+                        // Do not overwrite a kernel selected by a concurrent
+                        // `init`, such as from `force_backend`, which may be for
+                        // a newer backend than the one read above.
+                        let f = match FN.compare_exchange(
+                            detect as FnRaw,
+                            f,
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                        ) {
+                            Ok(_) => {
+                                let _ = super::SELECTED.compare_exchange(
+                                    super::super::UNINIT,
+                                    backend as u8,
+                                    Ordering::Relaxed,
+                                    Ordering::Relaxed,
+                                );
+                                f
+                            }
+                            Err(current) => current,
+                        };
+
+                        // SAFETY: By virtue of the caller contract, $fn_ty is a function
+                        //         pointer, which is always safe to transmute with `*mut ()`.
+                        //         Also, the kernel `f` is guaranteed to be supported since
+                        //         only supported backends are ever selected.
+                        unsafe {
+                            (mem::transmute::<FnRaw, $fn_ty>(f))($($arg),*)
+                        }
+                    }
+
+                    #[inline(always)]
+                    pub unsafe fn $name($($arg: $arg_ty),*) $(-> $ret_ty)? {
+                        // SAFETY: By virtue of the caller contract, $fn_ty is a function
+                        //         pointer, which is always safe to transmute with `*mut ()`.
+                        //         Also, the kernel `f` is guaranteed to be supported since
+                        //         only supported backends are ever selected.
+                        let f = FN.load(Ordering::Relaxed);
+                        (mem::transmute::<FnRaw, $fn_ty>(f))($($arg),*)
+                    }
                 }
             }
+        }
+    }
+}
 
-            #[inline(always)]
-            pub unsafe fn $name($($arg: $arg_ty),*) $(-> $ret_ty)? {
-                // SAFETY: By virtue of the caller contract, $fn_ty is a function
-                //         pointer, which is always safe to transmute with `*mut ()`.
-                //         Also, if `f` is the AVX2 routine, then it is guaranteed to be
-                //         supported since we checked the `avx2` feature.
-                let f = FN.load(Ordering::Relaxed);
-                (mem::transmute::<FnRaw, $fn_ty>(f))($($arg),*)
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        feature = "unstable",
+        target_feature = "avx512vl",
+        target_feature = "avx512bw",
+    ))] {
+        /// The backend statically dispatched to, since the target guarantees
+        /// that it is supported.
+        const STATIC_BACKEND: Option<Backend> = Some(Backend::Evex);
+    } else if #[cfg(target_feature = "avx2")] {
+        /// The backend statically dispatched to, since the target guarantees
+        /// that it is supported.
+        const STATIC_BACKEND: Option<Backend> = Some(Backend::Avx2);
+    } else {
+        /// The backend statically dispatched to, if any.
+        const STATIC_BACKEND: Option<Backend> = None;
+    }
+}
+
+/// Sentinel for a backend that has not yet been detected.
+const UNINIT: u8 = u8::MAX;
+
+/// The backend that kernels are selected from.
+static BACKEND: AtomicU8 = AtomicU8::new(UNINIT);
+
+/// Returns the backend requested by the environment, or otherwise the best
+/// backend supported by the CPU.
+fn detect() -> Backend {
+    #[cfg(feature = "std")]
+    {
+        if let Some(backend) = Backend::from_env() {
+            return backend;
+        }
+    }
+
+    if is_x86_feature_detected!("avx2") {
+        #[cfg(feature = "unstable")]
+        {
+            if is_x86_feature_detected!("avx512vl") && is_x86_feature_detected!("avx512bw") {
+                return Backend::Evex;
             }
         }
+        Backend::Avx2
+    } else if is_x86_feature_detected!("sse2") {
+        // SSE2 is supported for all x86_64 processors, but not all x86 processors.
+        Backend::Sse2
+    } else {
+        Backend::Fallback
     }
 }
 
-unsafe_ifuncs! {
-    fn i16::wmemchr(needle: i16, haystack: *const i16, len: usize) -> Option<usize>;
-    fn i32::wmemchr(needle: i32, haystack: *const i32, len: usize) -> Option<usize>;
+/// Returns the current backend, detecting it if needed.
+#[inline]
+pub(crate) fn backend() -> Backend {
+    if let Some(backend) = STATIC_BACKEND {
+        return backend;
+    }
+
+    let backend = BACKEND.load(Ordering::Relaxed);
+    if backend != UNINIT {
+        // Only valid backends are ever stored, other than `UNINIT`.
+        return Backend::from_u8(backend).unwrap();
+    }
+
+    let detected = detect();
+
+    // Do not overwrite a backend forced by another thread in the meantime.
+    match BACKEND.compare_exchange(UNINIT, detected as u8, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => detected,
+        Err(backend) => Backend::from_u8(backend).unwrap(),
+    }
 }
 
-pub(crate) struct Kernel;
+/// Returns `true` if the backend is supported by the CPU.
+///
+/// When the backend is statically dispatched to, no other backend can be used.
+pub(crate) fn is_supported(backend: Backend) -> bool {
+    if let Some(static_backend) = STATIC_BACKEND {
+        return backend == static_backend;
+    }
 
-impl KernelFn<u16> for Kernel {
-    fn kernel(needle: u16, haystack: &[u16]) -> Option<usize> {
-        unsafe {
-            i16::wmemchr(
-                needle as i16,
-                haystack.as_ptr() as *const i16,
-                haystack.len(),
-            )
+    match backend {
+        Backend::Fallback => true,
+        Backend::Portable => false,
+        Backend::Sse2 => is_x86_feature_detected!("sse2"),
+        Backend::Avx2 => is_x86_feature_detected!("avx2"),
+        #[cfg(feature = "unstable")]
+        Backend::Evex => {
+            is_x86_feature_detected!("avx2")
+                && is_x86_feature_detected!("avx512vl")
+                && is_x86_feature_detected!("avx512bw")
         }
+        #[cfg(not(feature = "unstable"))]
+        Backend::Evex => false,
+    }
+}
+
+/// Sets the backend, and reselects the kernels for every dispatched function.
+///
+/// The backend must be supported.
+pub(crate) fn set_backend(backend: Backend) {
+    debug_assert!(is_supported(backend));
+
+    BACKEND.store(backend as u8, Ordering::Relaxed);
+    init_ifuncs();
+}
+
+/// Detects the backend and selects the kernels for every dispatched function.
+pub(crate) fn init() {
+    backend();
+    init_ifuncs();
+}
+
+unsafe_ifuncs! {
+    mod i8 {
+        fn wmemchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize>;
+        fn wmemrchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize>;
+        fn wmemcount(needle: i8, haystack: *const i8, len: usize) -> usize;
+    }
+    mod i16 {
+        fn wmemchr(needle: i16, haystack: *const i16, len: usize) -> Option<usize>;
+        fn wmemchr_unaligned(needle: i16, haystack: *const i16, len: usize) -> Option<usize>;
+        fn wmemrchr(needle: i16, haystack: *const i16, len: usize) -> Option<usize>;
+        fn wmemcount(needle: i16, haystack: *const i16, len: usize) -> usize;
+        fn wcsnlen(s: *const i16, maxlen: usize) -> usize;
+        fn wcschr(s: *const i16, needle: i16) -> usize;
+    }
+    mod i32 {
+        fn wmemchr(needle: i32, haystack: *const i32, len: usize) -> Option<usize>;
+        fn wmemchr_unaligned(needle: i32, haystack: *const i32, len: usize) -> Option<usize>;
+        fn wmemrchr(needle: i32, haystack: *const i32, len: usize) -> Option<usize>;
+        fn wmemcount(needle: i32, haystack: *const i32, len: usize) -> usize;
+        fn wcsnlen(s: *const i32, maxlen: usize) -> usize;
+        fn wcschr(s: *const i32, needle: i32) -> usize;
     }
 }
 
-impl KernelFn<i16> for Kernel {
-    fn kernel(needle: i16, haystack: &[i16]) -> Option<usize> {
-        unsafe { i16::wmemchr(needle, haystack.as_ptr(), haystack.len()) }
+/// Returns the index of the first occurrence of a 16-bit wide character in a
+/// byte slice of native endian code units, which need not be aligned.
+#[inline]
+pub(crate) fn wmemchr_bytes16(needle: u16, haystack: &[u8]) -> Option<usize> {
+    unsafe {
+        i16::wmemchr_unaligned(
+            needle as i16,
+            haystack.as_ptr() as *const i16,
+            haystack.len() / 2,
+        )
     }
 }
 
-impl KernelFn<u32> for Kernel {
-    fn kernel(needle: u32, haystack: &[u32]) -> Option<usize> {
-        unsafe {
-            i32::wmemchr(
-                needle as i32,
-                haystack.as_ptr() as *const i32,
-                haystack.len(),
-            )
-        }
+/// Returns the index of the first occurrence of a 32-bit wide character in a
+/// byte slice of native endian code units, which need not be aligned.
+#[inline]
+pub(crate) fn wmemchr_bytes32(needle: u32, haystack: &[u8]) -> Option<usize> {
+    unsafe {
+        i32::wmemchr_unaligned(
+            needle as i32,
+            haystack.as_ptr() as *const i32,
+            haystack.len() / 4,
+        )
     }
 }
 
-impl KernelFn<i32> for Kernel {
-    fn kernel(needle: i32, haystack: &[i32]) -> Option<usize> {
-        unsafe { i32::wmemchr(needle, haystack.as_ptr(), haystack.len()) }
+/// Returns the number of 16-bit wide characters before the null terminator of
+/// a string, or `maxlen` if there is none within it.
+#[inline]
+pub(crate) unsafe fn wcsnlen16(s: *const u16, maxlen: usize) -> usize {
+    i16::wcsnlen(s as *const i16, maxlen)
+}
+
+/// Returns the number of 32-bit wide characters before the null terminator of
+/// a string, or `maxlen` if there is none within it.
+#[inline]
+pub(crate) unsafe fn wcsnlen32(s: *const u32, maxlen: usize) -> usize {
+    i32::wcsnlen(s as *const i32, maxlen)
+}
+
+/// Returns the index of the first occurrence of a 16-bit wide character in a
+/// string, or of its null terminator.
+#[inline]
+pub(crate) unsafe fn wcschr16(s: *const u16, needle: u16) -> usize {
+    i16::wcschr(s as *const i16, needle as i16)
+}
+
+/// Returns the index of the first occurrence of a 32-bit wide character in a
+/// string, or of its null terminator.
+#[inline]
+pub(crate) unsafe fn wcschr32(s: *const u32, needle: u32) -> usize {
+    i32::wcschr(s as *const i32, needle as i32)
+}
+
+/// Returns the index of the first 16-bit wide character in a slice that is
+/// equal to any of the needles, or [`None`] if none are found.
+///
+/// The AVX2 kernel is used with the AVX2 and EVEX backends, and the SSE4.2
+/// kernel with the SSE2 backend, if supported. Searches for more needles than
+/// a vector can hold use the naive implementation.
+#[inline]
+pub(crate) fn wmemchr_any16(needles: &[i16], haystack: &[i16]) -> Option<usize> {
+    if needles.is_empty() || needles.len() > sse42::i16::MAX_NEEDLES {
+        return naive::wmemchr_any(needles, haystack);
+    }
+
+    let needles_len = needles.len();
+    let needles_ptr = needles.as_ptr();
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+
+    // SAFETY: The kernels are only used if they are supported.
+    unsafe {
+        match backend() {
+            Backend::Evex | Backend::Avx2 => {
+                avx2::i16::wmemchr_any(needles_ptr, needles_len, ptr, len)
+            }
+            Backend::Sse2 if is_x86_feature_detected!("sse4.2") => {
+                sse42::i16::wmemchr_any(needles_ptr, needles_len, ptr, len)
+            }
+            _ => naive::wmemchr_any(needles, haystack),
+        }
     }
 }
+
+macro_rules! wmemchr_range16 {
+    ($($name:ident: $ty:ident => $kernel:ident;)*) => {
+        $(
+            /// Returns the index of the first 16-bit wide character in a slice
+            /// that is within an inclusive range, or [`None`] if none are found.
+            ///
+            /// The kernels are selected as for [`wmemchr_any16`].
+            #[inline]
+            pub(crate) fn $name(start: $ty, end: $ty, haystack: &[$ty]) -> Option<usize> {
+                if start > end {
+                    return None;
+                }
+
+                let len = haystack.len();
+                let ptr = haystack.as_ptr() as *const i16;
+
+                // SAFETY: The kernels are only used if they are supported.
+                unsafe {
+                    match backend() {
+                        Backend::Evex | Backend::Avx2 => avx2::i16::$kernel(start, end, ptr, len),
+                        Backend::Sse2 if is_x86_feature_detected!("sse4.2") => {
+                            sse42::i16::$kernel(start, end, ptr, len)
+                        }
+                        _ => naive::wmemchr_range(start..=end, haystack),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+wmemchr_range16! {
+    wmemchr_range16: u16 => wmemchr_range_unsigned;
+    wmemchr_range16_signed: i16 => wmemchr_range;
+}
+
+macro_rules! swap_endianness {
+    ($($name:ident: $ty:ident;)*) => {
+        $(
+            /// Reverses the byte order of each element of `data` in place.
+            ///
+            /// The AVX2 kernel is used with the AVX2 and EVEX backends, and the
+            /// SSSE3 kernel with the SSE2 backend, if supported.
+            #[inline]
+            pub(crate) unsafe fn $name(data: *mut $ty, len: usize) {
+                // SAFETY: The kernels are only used if they are supported.
+                match backend() {
+                    Backend::Evex | Backend::Avx2 => avx2::$ty::swap_endianness(data, len),
+                    Backend::Sse2 if is_x86_feature_detected!("ssse3") => {
+                        ssse3::$ty::swap_endianness(data, len)
+                    }
+                    _ => {
+                        // Without `pshufb`, the compiler generates better code
+                        // for swapping each element than we can by hand.
+                        for i in 0..len {
+                            let ptr = data.add(i);
+                            *ptr = (*ptr).swap_bytes();
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+swap_endianness! {
+    swap_endianness16: i16;
+    swap_endianness32: i32;
+}
+
+pub(crate) struct Kernel;
+
+macro_rules! impl_kernel {
+    ($($ty:ty => $ity:ident;)*) => {
+        $(
+            impl KernelFn<$ty> for Kernel {
+                #[inline]
+                unsafe fn kernel(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    $ity::wmemchr(needle as $ity, haystack as *const $ity, len)
+                }
+
+                #[inline]
+                unsafe fn kernel_reverse(needle: $ty, haystack: *const $ty, len: usize) -> Option<usize> {
+                    $ity::wmemrchr(needle as $ity, haystack as *const $ity, len)
+                }
+
+                #[inline]
+                unsafe fn kernel_count(needle: $ty, haystack: *const $ty, len: usize) -> usize {
+                    $ity::wmemcount(needle as $ity, haystack as *const $ity, len)
+                }
+
+                #[inline]
+                fn backend() -> Backend {
+                    $ity::backend()
+                }
+            }
+        )*
+    };
+}
+
+impl_kernel! {
+    u8 => i8;
+    i8 => i8;
+    u16 => i16;
+    i16 => i16;
+    u32 => i32;
+    i32 => i32;
+    // A `char` is represented in memory as its `u32` scalar value.
+    char => i32;
+}
@@ -1,170 +1,106 @@
-use core::arch::x86_64::*;
 use core::mem;
 use core::num::NonZeroI32;
 
+use crate::vector;
+use crate::x86_64::arch::*;
+
 const VECTOR_SIZE: usize = mem::size_of::<__m128i>();
-const VECTOR_ALIGN: usize = VECTOR_SIZE - 1;
 
 const VECTOR_ELEMENTS: usize = VECTOR_SIZE / mem::size_of::<i32>();
 
-const LOOP_SIZE: usize = 4 * VECTOR_SIZE;
-const LOOP_ELEMENTS: usize = 4 * VECTOR_ELEMENTS;
-
 #[target_feature(enable = "sse2")]
 pub unsafe fn wmemchr(needle: i32, haystack: *const i32, len: usize) -> Option<usize> {
-    let start = haystack;
-    let end = haystack.add(len);
-    let mut ptr = start;
-
-    debug_assert!(start <= end);
-
-    // If haystack length is less than number of elements in a packed vector,
-    // then do a simple forward search.
-    if len < VECTOR_ELEMENTS {
-        while ptr < end {
-            if *ptr == needle {
-                return Some(ptr.offset_from(start) as usize);
-            }
-            ptr = ptr.add(1);
-        }
-        return None;
-    }
-
-    debug_assert!(end.offset_from(start) as usize >= VECTOR_ELEMENTS);
-
-    // Broadcast the needle across the elements of the vector.
-    let v_needle = _mm_set1_epi32(needle);
-
-    if let Some(pos) = forward_search_unaligned(start, end, ptr, v_needle) {
-        return Some(pos);
+    // If haystack is shorter than two vectors, then search it with at most
+    // two overlapping loads.
+    if len < 2 * VECTOR_ELEMENTS {
+        return wmemchr_tiny(needle, haystack, len);
     }
 
-    // Align `ptr` to improve read performance in loop.
-    // This calculation is based on byte pointer, and not the scaled addition.
-    ptr = {
-        let align_offset = VECTOR_SIZE - ((start as usize) & VECTOR_ALIGN);
-        (start as *const u8).add(align_offset) as *const i32
-    };
+    vector::wmemchr::<i32, __m128i>(needle, haystack, len, None)
+}
 
-    // The pointer will advance at least one element and at most by the
-    // number of elements in one vector.
-    debug_assert!(start < ptr);
-    debug_assert!(ptr.offset_from(start) as usize <= VECTOR_ELEMENTS);
-
-    // 64 byte (16 element) loop.
-    if let Some(loop_end) = (end as usize).checked_sub(LOOP_SIZE) {
-        while (ptr as usize) <= loop_end {
-            debug_assert_eq!((ptr as usize) % VECTOR_SIZE, 0);
-
-            let p = ptr as *const __m128i;
-
-            // Load 4 vectors of characters.
-            let a = _mm_load_si128(p);
-            let b = _mm_load_si128(p.add(1));
-            let c = _mm_load_si128(p.add(2));
-            let d = _mm_load_si128(p.add(3));
-
-            // Look for needle in vectors.
-            let eq_a = _mm_cmpeq_epi32(a, v_needle);
-            let eq_b = _mm_cmpeq_epi32(b, v_needle);
-            let eq_c = _mm_cmpeq_epi32(c, v_needle);
-            let eq_d = _mm_cmpeq_epi32(d, v_needle);
-
-            // Determine if any vectors contained the needle.
-            let or_ab = _mm_or_si128(eq_a, eq_b);
-            let or_cd = _mm_or_si128(eq_c, eq_d);
-            let or = _mm_or_si128(or_ab, or_cd);
-
-            // If any vector contains the needle, we will search for it in each vector.
-            if _mm_movemask_epi8(or) != 0 {
-                // Keep track of the offset from the start of the haystack.
-                let mut offset = ptr.offset_from(start) as usize;
-
-                let mask = _mm_movemask_epi8(eq_a);
-                if let Some(mask) = NonZeroI32::new(mask) {
-                    return Some(offset + forward_pos(mask));
-                }
-                offset += VECTOR_ELEMENTS;
-
-                let mask = _mm_movemask_epi8(eq_b);
-                if let Some(mask) = NonZeroI32::new(mask) {
-                    return Some(offset + forward_pos(mask));
-                }
-                offset += VECTOR_ELEMENTS;
-
-                let mask = _mm_movemask_epi8(eq_c);
-                if let Some(mask) = NonZeroI32::new(mask) {
-                    return Some(offset + forward_pos(mask));
-                }
-                offset += VECTOR_ELEMENTS;
-
-                let mask = _mm_movemask_epi8(eq_d);
-                debug_assert_ne!(mask, 0);
-                let mask = NonZeroI32::new_unchecked(mask);
-                return Some(offset + forward_pos(mask));
-            }
-
-            ptr = ptr.add(LOOP_ELEMENTS);
-        }
+/// Like [`wmemchr`], except that `haystack` need only be aligned to a byte
+/// boundary, rather than to the size of an element.
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemchr_unaligned(needle: i32, haystack: *const i32, len: usize) -> Option<usize> {
+    // If haystack is shorter than two vectors, then search it with at most
+    // two overlapping loads.
+    if len < 2 * VECTOR_ELEMENTS {
+        return wmemchr_tiny(needle, haystack, len);
     }
 
-    // 16 byte (4 element) loop.
-    if let Some(loop_end) = (end as usize).checked_sub(VECTOR_SIZE) {
-        while (ptr as usize) <= loop_end {
-            debug_assert_eq!((ptr as usize) % VECTOR_SIZE, 0);
-
-            let chunk = _mm_load_si128(ptr as *const __m128i);
-            let eq = _mm_cmpeq_epi32(chunk, v_needle);
+    vector::wmemchr_unaligned::<i32, __m128i>(needle, haystack, len)
+}
 
-            let mask = _mm_movemask_epi8(eq);
-            if let Some(mask) = NonZeroI32::new(mask) {
-                let offset = ptr.offset_from(start) as usize;
-                return Some(offset + forward_pos(mask));
-            }
+/// Searches a haystack for the last occurrence of `needle`.
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemrchr(needle: i32, haystack: *const i32, len: usize) -> Option<usize> {
+    vector::wmemrchr::<i32, __m128i>(needle, haystack, len)
+}
 
-            ptr = ptr.add(VECTOR_ELEMENTS);
-        }
-    }
+/// Counts the occurrences of `needle` in a haystack.
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemcount(needle: i32, haystack: *const i32, len: usize) -> usize {
+    vector::wmemcount::<i32, __m128i>(needle, haystack, len)
+}
 
-    // Invariant: `0 <= end - ptr < VECTOR_SIZE`.
+/// Returns the number of elements before the null terminator of a string, or
+/// `maxlen` if there is none within it, with aligned loads.
+#[target_feature(enable = "sse2")]
+pub unsafe fn wcsnlen(s: *const i32, maxlen: usize) -> usize {
+    vector::wcsnlen::<i32, __m128i>(s, maxlen)
+}
 
-    // We can search the remaining elements by shifting `ptr` back and doing an
-    // unaligned forward search.
+/// Returns the index of the first occurrence of `needle` in a string, or of
+/// its null terminator, with aligned loads.
+#[target_feature(enable = "sse2")]
+pub unsafe fn wcschr(s: *const i32, needle: i32) -> usize {
+    vector::wcschr::<i32, __m128i>(s, needle)
+}
 
-    if ptr < end {
-        let remaining = end.offset_from(ptr) as usize;
+/// Searches a haystack of fewer than 32 bytes, with at most two overlapping
+/// loads, and without branching on the contents of the haystack.
+#[inline]
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemchr_tiny(needle: i32, haystack: *const i32, len: usize) -> Option<usize> {
+    let size = len * mem::size_of::<i32>();
+    let bytes = haystack as *const u8;
+
+    debug_assert!(size < 2 * VECTOR_SIZE);
+
+    // Load the first and last `width` bytes of the haystack, which overlap
+    // unless the haystack is exactly `2 * width` bytes.
+    let (width, first, last) = match size {
+        0 => return None,
+        16..=31 => (
+            16,
+            _mm_loadu_si128(bytes as *const __m128i),
+            _mm_loadu_si128(bytes.add(size - 16) as *const __m128i),
+        ),
+        8..=15 => (
+            8,
+            _mm_loadl_epi64(bytes as *const __m128i),
+            _mm_loadl_epi64(bytes.add(size - 8) as *const __m128i),
+        ),
+        _ => (
+            4,
+            _mm_cvtsi32_si128((bytes as *const i32).read_unaligned()),
+            _mm_cvtsi32_si128((bytes.add(size - 4) as *const i32).read_unaligned()),
+        ),
+    };
 
-        debug_assert!(remaining < VECTOR_ELEMENTS);
-        ptr = ptr.sub(VECTOR_ELEMENTS - remaining);
-        debug_assert_eq!(end.offset_from(ptr) as usize, VECTOR_ELEMENTS);
+    // Broadcast the needle across the elements of the vector.
+    let v_needle = _mm_set1_epi32(needle);
 
-        return forward_search_unaligned(start, end, ptr, v_needle);
-    }
+    let first = _mm_movemask_epi8(_mm_cmpeq_epi32(first, v_needle)) as u32;
+    let last = _mm_movemask_epi8(_mm_cmpeq_epi32(last, v_needle)) as u32;
 
-    None
-}
+    // Discard matches past the end of each load, then shift the matches in
+    // the last load to their offset in the haystack.
+    let valid = (1 << width) - 1;
+    let mask = (first & valid) | ((last & valid) << (size - width));
 
-#[inline]
-#[target_feature(enable = "sse2")]
-unsafe fn forward_search_unaligned(
-    start: *const i32,
-    end: *const i32,
-    ptr: *const i32,
-    v_needle: __m128i,
-) -> Option<usize> {
-    debug_assert!(start <= ptr);
-    debug_assert!(end.offset_from(ptr) as usize >= VECTOR_ELEMENTS);
-
-    let chunk = _mm_loadu_si128(ptr as *const __m128i);
-    let eq = _mm_cmpeq_epi32(chunk, v_needle);
-
-    let mask = _mm_movemask_epi8(eq);
-    if let Some(mask) = NonZeroI32::new(mask) {
-        let offset = ptr.offset_from(start) as usize;
-        Some(offset + forward_pos(mask))
-    } else {
-        None
-    }
+    NonZeroI32::new(mask as i32).map(forward_pos)
 }
 
 /// Get the forward position in a mask obtained from `_mm_movemask_epi8`.
@@ -180,6 +116,6 @@ unsafe fn forward_search_unaligned(
 /// We take advantage of the fact that we check the mask is non-zero, in order
 /// to optimise this function.
 #[inline(always)]
-pub fn forward_pos(mask: NonZeroI32) -> usize {
+fn forward_pos(mask: NonZeroI32) -> usize {
     (bsf!(mask) as usize) >> 2
 }
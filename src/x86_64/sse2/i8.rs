@@ -0,0 +1,103 @@
+use core::mem;
+use core::num::NonZeroI32;
+
+use crate::vector;
+use crate::x86_64::arch::*;
+
+const VECTOR_SIZE: usize = mem::size_of::<__m128i>();
+
+const VECTOR_ELEMENTS: usize = VECTOR_SIZE / mem::size_of::<i8>();
+
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize> {
+    // If haystack is shorter than two vectors, then search it with at most
+    // two overlapping loads.
+    if len < 2 * VECTOR_ELEMENTS {
+        return wmemchr_tiny(needle, haystack, len);
+    }
+
+    vector::wmemchr::<i8, __m128i>(needle, haystack, len, None)
+}
+
+/// Searches a haystack for the last occurrence of `needle`.
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemrchr(needle: i8, haystack: *const i8, len: usize) -> Option<usize> {
+    vector::wmemrchr::<i8, __m128i>(needle, haystack, len)
+}
+
+/// Counts the occurrences of `needle` in a haystack.
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemcount(needle: i8, haystack: *const i8, len: usize) -> usize {
+    vector::wmemcount::<i8, __m128i>(needle, haystack, len)
+}
+
+/// Searches a haystack of fewer than 32 bytes, with at most two overlapping
+/// loads, and without branching on the contents of the haystack.
+#[inline]
+#[target_feature(enable = "sse2")]
+pub unsafe fn wmemchr_tiny(needle: i8, haystack: *const i8, len: usize) -> Option<usize> {
+    let size = len * mem::size_of::<i8>();
+    let bytes = haystack as *const u8;
+
+    debug_assert!(size < 2 * VECTOR_SIZE);
+
+    // Load the first and last `width` bytes of the haystack, which overlap
+    // unless the haystack is exactly `2 * width` bytes.
+    let (width, first, last) = match size {
+        0 => return None,
+        16..=31 => (
+            16,
+            _mm_loadu_si128(bytes as *const __m128i),
+            _mm_loadu_si128(bytes.add(size - 16) as *const __m128i),
+        ),
+        8..=15 => (
+            8,
+            _mm_loadl_epi64(bytes as *const __m128i),
+            _mm_loadl_epi64(bytes.add(size - 8) as *const __m128i),
+        ),
+        4..=7 => (
+            4,
+            _mm_cvtsi32_si128((bytes as *const i32).read_unaligned()),
+            _mm_cvtsi32_si128((bytes.add(size - 4) as *const i32).read_unaligned()),
+        ),
+        2..=3 => (
+            2,
+            _mm_cvtsi32_si128(i32::from((bytes as *const i16).read_unaligned())),
+            _mm_cvtsi32_si128(i32::from(
+                (bytes.add(size - 2) as *const i16).read_unaligned(),
+            )),
+        ),
+        _ => (
+            1,
+            _mm_cvtsi32_si128(i32::from(*bytes)),
+            _mm_cvtsi32_si128(i32::from(*bytes)),
+        ),
+    };
+
+    // Broadcast the needle across the elements of the vector.
+    let v_needle = _mm_set1_epi8(needle);
+
+    let first = _mm_movemask_epi8(_mm_cmpeq_epi8(first, v_needle)) as u32;
+    let last = _mm_movemask_epi8(_mm_cmpeq_epi8(last, v_needle)) as u32;
+
+    // Discard matches past the end of each load, then shift the matches in
+    // the last load to their offset in the haystack.
+    let valid = (1 << width) - 1;
+    let mask = (first & valid) | ((last & valid) << (size - width));
+
+    NonZeroI32::new(mask as i32).map(forward_pos)
+}
+
+/// Get the forward position in a mask obtained from `_mm_movemask_epi8`.
+///
+/// Since each element is a single byte, the position of the first set bit is
+/// the position of the match.
+///
+/// # Notes
+///
+/// We take advantage of the fact that we check the mask is non-zero, in order
+/// to optimise this function.
+#[inline(always)]
+fn forward_pos(mask: NonZeroI32) -> usize {
+    bsf!(mask) as usize
+}
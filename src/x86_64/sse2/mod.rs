@@ -1,2 +1,56 @@
+use core::mem;
+
+use crate::vector::Vector;
+use crate::x86_64::arch::*;
+
 pub mod i16;
 pub mod i32;
+pub mod i8;
+
+macro_rules! impl_vector {
+    ($ty:ident, $set1:ident, $cmpeq:ident) => {
+        unsafe impl Vector<$ty> for __m128i {
+            const LANES: usize = mem::size_of::<__m128i>() / mem::size_of::<$ty>();
+            // There is no `_mm_movemask_epi16`, so each element sets a bit for
+            // each of its bytes.
+            const MASK_STRIDE: usize = mem::size_of::<$ty>();
+            const MASK_BITS: u32 = mem::size_of::<$ty>() as u32;
+
+            type Mask = __m128i;
+
+            #[inline(always)]
+            unsafe fn splat(value: $ty) -> __m128i {
+                $set1(value)
+            }
+
+            #[inline(always)]
+            unsafe fn load_aligned(ptr: *const $ty) -> __m128i {
+                _mm_load_si128(ptr as *const __m128i)
+            }
+
+            #[inline(always)]
+            unsafe fn load_unaligned(ptr: *const $ty) -> __m128i {
+                _mm_loadu_si128(ptr as *const __m128i)
+            }
+
+            #[inline(always)]
+            unsafe fn cmpeq(self, other: __m128i) -> __m128i {
+                $cmpeq(self, other)
+            }
+
+            #[inline(always)]
+            unsafe fn or(a: __m128i, b: __m128i) -> __m128i {
+                _mm_or_si128(a, b)
+            }
+
+            #[inline(always)]
+            unsafe fn movemask(mask: __m128i) -> u64 {
+                u64::from(_mm_movemask_epi8(mask) as u32)
+            }
+        }
+    };
+}
+
+impl_vector!(i8, _mm_set1_epi8, _mm_cmpeq_epi8);
+impl_vector!(i16, _mm_set1_epi16, _mm_cmpeq_epi16);
+impl_vector!(i32, _mm_set1_epi32, _mm_cmpeq_epi32);
@@ -0,0 +1,142 @@
+use core::{mem, ptr};
+
+use crate::x86_64::arch::*;
+
+const VECTOR_SIZE: usize = mem::size_of::<__m128i>();
+
+const VECTOR_ELEMENTS: usize = VECTOR_SIZE / mem::size_of::<i16>();
+
+/// The most needles that can be searched for at once.
+pub const MAX_NEEDLES: usize = VECTOR_ELEMENTS;
+
+macro_rules! cmpestri_search {
+    ($(
+        $(#[$attr:meta])*
+        $vis:vis unsafe fn $name:ident($set:ident: *const i16, $set_len:ident: usize) => $mode:expr;
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[target_feature(enable = "sse4.2")]
+            $vis unsafe fn $name(
+                $set: *const i16,
+                $set_len: usize,
+                haystack: *const i16,
+                len: usize,
+            ) -> Option<usize> {
+                const MODE: i32 = $mode | _SIDD_LEAST_SIGNIFICANT;
+
+                debug_assert!($set_len <= VECTOR_ELEMENTS);
+
+                let set = load_partial($set, $set_len);
+                let set_len = $set_len as i32;
+
+                // If haystack length is less than number of elements in a packed
+                // vector, then copy it into a vector and search its length.
+                if len < VECTOR_ELEMENTS {
+                    let chunk = load_partial(haystack, len);
+
+                    // The index is the number of elements if there is no match.
+                    let pos = _mm_cmpestri(set, set_len, chunk, len as i32, MODE) as usize;
+                    return if pos < len { Some(pos) } else { None };
+                }
+
+                let start = haystack;
+                let end = haystack.add(len);
+                let mut ptr = start;
+
+                // 16 byte (8 element) loop.
+                while end.offset_from(ptr) as usize >= VECTOR_ELEMENTS {
+                    if let Some(pos) = forward_search_unaligned::<MODE>(start, ptr, set, set_len) {
+                        return Some(pos);
+                    }
+                    ptr = ptr.add(VECTOR_ELEMENTS);
+                }
+
+                // Invariant: `0 <= end - ptr < VECTOR_ELEMENTS`.
+
+                // We can search the remaining elements by shifting `ptr` back and
+                // doing an unaligned forward search.
+
+                if ptr < end {
+                    ptr = end.sub(VECTOR_ELEMENTS);
+                    return forward_search_unaligned::<MODE>(start, ptr, set, set_len);
+                }
+
+                None
+            }
+        )*
+    };
+}
+
+cmpestri_search! {
+    /// Searches for the first element equal to any of the `needles_len`
+    /// needles, of which there are at most [`MAX_NEEDLES`].
+    pub unsafe fn wmemchr_any(needles: *const i16, needles_len: usize) =>
+        _SIDD_UWORD_OPS | _SIDD_CMP_EQUAL_ANY;
+
+    /// Searches for the first element within any of the `ranges_len / 2`
+    /// inclusive ranges of signed elements, given as pairs of bounds.
+    unsafe fn wmemchr_ranges(ranges: *const i16, ranges_len: usize) =>
+        _SIDD_SWORD_OPS | _SIDD_CMP_RANGES;
+
+    /// Searches for the first element within any of the `ranges_len / 2`
+    /// inclusive ranges of unsigned elements, given as pairs of bounds.
+    unsafe fn wmemchr_ranges_unsigned(ranges: *const i16, ranges_len: usize) =>
+        _SIDD_UWORD_OPS | _SIDD_CMP_RANGES;
+}
+
+/// Searches for the first element within the inclusive range of signed
+/// elements from `start` to `end`.
+#[target_feature(enable = "sse4.2")]
+pub unsafe fn wmemchr_range(
+    start: i16,
+    end: i16,
+    haystack: *const i16,
+    len: usize,
+) -> Option<usize> {
+    let range = [start, end];
+    wmemchr_ranges(range.as_ptr(), range.len(), haystack, len)
+}
+
+/// Searches for the first element within the inclusive range of unsigned
+/// elements from `start` to `end`.
+#[target_feature(enable = "sse4.2")]
+pub unsafe fn wmemchr_range_unsigned(
+    start: u16,
+    end: u16,
+    haystack: *const i16,
+    len: usize,
+) -> Option<usize> {
+    let range = [start as i16, end as i16];
+    wmemchr_ranges_unsigned(range.as_ptr(), range.len(), haystack, len)
+}
+
+#[inline]
+#[target_feature(enable = "sse4.2")]
+unsafe fn forward_search_unaligned<const MODE: i32>(
+    start: *const i16,
+    ptr: *const i16,
+    set: __m128i,
+    set_len: i32,
+) -> Option<usize> {
+    let chunk = _mm_loadu_si128(ptr as *const __m128i);
+
+    let pos = _mm_cmpestri(set, set_len, chunk, VECTOR_ELEMENTS as i32, MODE) as usize;
+    if pos < VECTOR_ELEMENTS {
+        let offset = ptr.offset_from(start) as usize;
+        Some(offset + pos)
+    } else {
+        None
+    }
+}
+
+/// Loads fewer than a vector of elements into a vector, without reading past
+/// the last element.
+#[inline(always)]
+unsafe fn load_partial(data: *const i16, len: usize) -> __m128i {
+    debug_assert!(len <= VECTOR_ELEMENTS);
+
+    let mut buf = [0i16; VECTOR_ELEMENTS];
+    ptr::copy_nonoverlapping(data, buf.as_mut_ptr(), len);
+    _mm_loadu_si128(buf.as_ptr() as *const __m128i)
+}
@@ -0,0 +1,38 @@
+use core::mem;
+
+use crate::x86_64::arch::*;
+
+const VECTOR_SIZE: usize = mem::size_of::<__m128i>();
+
+const VECTOR_ELEMENTS: usize = VECTOR_SIZE / mem::size_of::<i32>();
+
+#[target_feature(enable = "ssse3")]
+pub unsafe fn swap_endianness(data: *mut i32, len: usize) {
+    let start = data;
+    let end = data.add(len);
+    let mut ptr = start;
+
+    debug_assert!(start <= end);
+
+    // Shuffle the bytes within each 32-bit lane.
+    let shuffle = _mm_setr_epi8(3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12);
+
+    // 16 byte (4 element) loop.
+    if let Some(loop_end) = (end as usize).checked_sub(VECTOR_SIZE) {
+        while (ptr as usize) <= loop_end {
+            let p = ptr as *mut __m128i;
+
+            let chunk = _mm_loadu_si128(p);
+            _mm_storeu_si128(p, _mm_shuffle_epi8(chunk, shuffle));
+
+            ptr = ptr.add(VECTOR_ELEMENTS);
+        }
+    }
+
+    // Unlike searching, the remaining elements cannot be handled by shifting
+    // `ptr` back, since elements would be swapped twice.
+    while ptr < end {
+        *ptr = (*ptr).swap_bytes();
+        ptr = ptr.add(1);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod i16;
+pub mod i32;
@@ -0,0 +1,112 @@
+use wmemchr::{
+    backend, force_backend, naive, swap_endianness, wmemchr, wmemcount, wmemrchr, Backend,
+};
+
+#[test]
+fn detected_backend_is_supported() {
+    wmemchr::init();
+
+    assert!(backend::<u8>().is_supported());
+    assert!(backend::<u16>().is_supported());
+    assert!(backend::<u32>().is_supported());
+    assert!(backend::<char>().is_supported());
+}
+
+#[test]
+fn force_each_backend() {
+    let haystack: Vec<u16> = (0..1000).map(|i| (i % 251) as u16).collect();
+
+    for &b in Backend::ALL.iter() {
+        if b.is_supported() {
+            assert_eq!(force_backend(b), Ok(()));
+            assert_eq!(backend::<u8>(), b);
+            assert_eq!(backend::<u16>(), b);
+            assert_eq!(backend::<u32>(), b);
+
+            for needle in 240..260 {
+                assert_eq!(
+                    wmemchr(needle, &haystack),
+                    naive::wmemchr(needle, &haystack),
+                    "backend: {}",
+                    b,
+                );
+                assert_eq!(
+                    wmemrchr(needle, &haystack),
+                    naive::wmemrchr(needle, &haystack),
+                    "backend: {}",
+                    b,
+                );
+                assert_eq!(
+                    wmemcount(needle, &haystack),
+                    naive::wmemcount(needle, &haystack),
+                    "backend: {}",
+                    b,
+                );
+            }
+
+            let mut swapped = haystack.clone();
+            swap_endianness(&mut swapped);
+            assert!(
+                swapped
+                    .iter()
+                    .zip(&haystack)
+                    .all(|(s, c)| *s == c.swap_bytes()),
+                "backend: {}",
+                b,
+            );
+        } else {
+            let err = force_backend(b).unwrap_err();
+            assert_eq!(err.backend(), b);
+        }
+    }
+}
+
+#[cfg(all(
+    not(miri),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(target_feature = "avx2"),
+))]
+#[test]
+fn supported_backends_match_std_detection() {
+    // Without the `std` feature, the crate detects CPU features with `cpuid`.
+    assert_eq!(
+        Backend::Sse2.is_supported(),
+        std::is_x86_feature_detected!("sse2"),
+    );
+    assert_eq!(
+        Backend::Avx2.is_supported(),
+        std::is_x86_feature_detected!("avx2"),
+    );
+
+    #[cfg(feature = "unstable")]
+    assert_eq!(
+        Backend::Evex.is_supported(),
+        std::is_x86_feature_detected!("avx2")
+            && std::is_x86_feature_detected!("avx512vl")
+            && std::is_x86_feature_detected!("avx512bw"),
+    );
+}
+
+#[cfg(all(
+    not(miri),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "avx2",
+))]
+#[test]
+fn static_backend_is_only_supported() {
+    let expected = if cfg!(all(
+        feature = "unstable",
+        target_feature = "avx512vl",
+        target_feature = "avx512bw",
+    )) {
+        Backend::Evex
+    } else {
+        Backend::Avx2
+    };
+
+    assert_eq!(backend::<u16>(), expected);
+
+    for &b in Backend::ALL.iter() {
+        assert_eq!(b.is_supported(), b == expected, "backend: {}", b);
+    }
+}
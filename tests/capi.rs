@@ -0,0 +1,204 @@
+#![cfg(feature = "capi")]
+
+use std::ptr;
+
+// The exports are only linked if the crate is used.
+use wmemchr as _;
+
+extern "C" {
+    fn wmemchr16(s: *const u16, c: u16, n: usize) -> *mut u16;
+    fn wmemchr32(s: *const u32, c: u32, n: usize) -> *mut u32;
+    fn wmemrchr16(s: *const u16, c: u16, n: usize) -> *mut u16;
+    fn wmemrchr32(s: *const u32, c: u32, n: usize) -> *mut u32;
+    fn wcslen16(s: *const u16) -> usize;
+    fn wcslen32(s: *const u32) -> usize;
+    fn wcsnlen16(s: *const u16, maxlen: usize) -> usize;
+    fn wcsnlen32(s: *const u32, maxlen: usize) -> usize;
+}
+
+/// The functions declared above, which must match the header.
+const EXPORTS: &[&str] = &[
+    "wmemchr16",
+    "wmemchr32",
+    "wmemrchr16",
+    "wmemrchr32",
+    "wcslen16",
+    "wcslen32",
+    "wcsnlen16",
+    "wcsnlen32",
+];
+
+macro_rules! tests {
+    ($($ty:ident: $wmemchr:ident, $wmemrchr:ident, $wcslen:ident, $wcsnlen:ident;)*) => {
+        $(
+            mod $ty {
+                use super::*;
+
+                #[test]
+                fn wmemchr() {
+                    let haystack: Vec<$ty> = (0..100).map(|i| (i % 61) as $ty).collect();
+                    let ptr = haystack.as_ptr();
+
+                    for len in 0..haystack.len() {
+                        for &needle in &[0, 30, 60, 70] {
+                            let to_ptr = |pos: Option<usize>| {
+                                pos.map_or(ptr::null_mut(), |pos| unsafe { ptr.add(pos) as *mut $ty })
+                            };
+
+                            unsafe {
+                                assert_eq!(
+                                    $wmemchr(ptr, needle, len),
+                                    to_ptr(haystack[..len].iter().position(|&c| c == needle)),
+                                );
+                                assert_eq!(
+                                    $wmemrchr(ptr, needle, len),
+                                    to_ptr(haystack[..len].iter().rposition(|&c| c == needle)),
+                                );
+                            }
+                        }
+                    }
+
+                    unsafe {
+                        assert_eq!($wmemchr(ptr::null(), 0, 0), ptr::null_mut());
+                        assert_eq!($wmemrchr(ptr::null(), 0, 0), ptr::null_mut());
+                    }
+                }
+
+                #[test]
+                fn wcslen() {
+                    let mut s: Vec<$ty> = (1..=100).collect();
+                    s.push(0);
+                    s.push(1);
+
+                    for start in 0..s.len() - 1 {
+                        let expected = 100usize.saturating_sub(start);
+
+                        unsafe {
+                            assert_eq!($wcslen(s.as_ptr().add(start)), expected);
+
+                            for maxlen in 0..s.len() - start {
+                                assert_eq!(
+                                    $wcsnlen(s.as_ptr().add(start), maxlen),
+                                    expected.min(maxlen),
+                                );
+                            }
+                        }
+                    }
+
+                    unsafe {
+                        assert_eq!($wcsnlen(ptr::null(), 0), 0);
+                    }
+                }
+
+                /// Places strings at the end of a page followed by a guard
+                /// page, so that reading past the null terminator, or past
+                /// `maxlen` without one, crashes.
+                #[cfg(all(target_os = "linux", not(miri)))]
+                #[test]
+                fn wcslen_guard_page() {
+                    use wmemchr::{force_backend, Backend};
+
+                    let page = GuardedPage::new();
+                    let end = page.end() as *mut $ty;
+                    let max_len = 256 / std::mem::size_of::<$ty>();
+
+                    for &backend in Backend::ALL.iter() {
+                        if force_backend(backend).is_err() {
+                            continue;
+                        }
+
+                        for len in 0..max_len {
+                            unsafe {
+                                let s = end.sub(len + 1);
+                                for i in 0..len {
+                                    *s.add(i) = (i % 100 + 1) as $ty;
+                                }
+                                *s.add(len) = 0;
+
+                                assert_eq!($wcslen(s), len);
+                                assert_eq!($wcsnlen(s, len), len);
+                                assert_eq!($wcsnlen(s, usize::MAX), len);
+
+                                // Without a null terminator before the guard page.
+                                *s.add(len) = 1;
+                                assert_eq!($wcsnlen(s, len + 1), len + 1);
+                            }
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+/// A readable page of memory, followed by a guard page that cannot be
+/// accessed.
+#[cfg(all(target_os = "linux", not(miri)))]
+struct GuardedPage {
+    ptr: *mut u8,
+    page_size: usize,
+}
+
+#[cfg(all(target_os = "linux", not(miri)))]
+impl GuardedPage {
+    fn new() -> GuardedPage {
+        unsafe {
+            let page_size = libc::sysconf(libc::_SC_PAGESIZE) as usize;
+
+            let ptr = libc::mmap(
+                ptr::null_mut(),
+                2 * page_size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            assert_ne!(ptr, libc::MAP_FAILED, "failed to map pages");
+
+            let ptr = ptr as *mut u8;
+            let ret = libc::mprotect(
+                ptr.add(page_size) as *mut libc::c_void,
+                page_size,
+                libc::PROT_NONE,
+            );
+            assert_eq!(ret, 0, "failed to protect guard page");
+
+            GuardedPage { ptr, page_size }
+        }
+    }
+
+    /// Returns a pointer to the end of the readable page.
+    fn end(&self) -> *mut u8 {
+        unsafe { self.ptr.add(self.page_size) }
+    }
+}
+
+#[cfg(all(target_os = "linux", not(miri)))]
+impl Drop for GuardedPage {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, 2 * self.page_size);
+        }
+    }
+}
+
+tests! {
+    u16: wmemchr16, wmemrchr16, wcslen16, wcsnlen16;
+    u32: wmemchr32, wmemrchr32, wcslen32, wcsnlen32;
+}
+
+#[test]
+fn header_declares_exports() {
+    let header = include_str!("../include/wmemchr.h");
+
+    let declared: Vec<&str> = header
+        .lines()
+        .filter(|line| !line.starts_with(&['/', ' ', '#'][..]) && line.ends_with(");"))
+        .filter_map(|line| {
+            let name = &line[..line.find('(')?];
+            name.rsplit(&[' ', '*'][..]).next()
+        })
+        .collect();
+
+    assert_eq!(declared, EXPORTS);
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "checked")]
+
+use wmemchr::{naive, register_kernel, unregister_kernel, Kernel};
+
+#[test]
+fn agreeing_searches() {
+    let haystack: Vec<u16> = (0..1000).map(|i| (i % 251) as u16).collect();
+
+    for needle in 240..260 {
+        wmemchr::wmemchr(needle, &haystack);
+        wmemchr::wmemchr_any(&[needle, 7], &haystack);
+        wmemchr::wmemchr_range(needle..=needle + 3, &haystack);
+    }
+
+    let bytes = b"_f\0o\0o\0 \0b\0a\0r\0";
+    for offset in 0..4 {
+        wmemchr::wmemchr_bytes_le(u16::from(b'o'), &bytes[offset..]);
+        wmemchr::wmemchr32_bytes_be(u32::from(b'o'), &bytes[offset..]);
+    }
+}
+
+#[test]
+fn agreeing_backend_searches() {
+    #[cfg(all(not(miri), target_arch = "x86"))]
+    use wmemchr::x86 as x86_64;
+    #[cfg(all(not(miri), target_arch = "x86_64"))]
+    use wmemchr::x86_64;
+
+    let haystack: Vec<u32> = (0..1000).map(|i| i % 251).collect();
+
+    for needle in 240..260 {
+        wmemchr::fallback::wmemchr(needle, &haystack);
+        wmemchr::fallback::wmemrchr(needle, &haystack);
+        wmemchr::fallback::wmemcount(needle, &haystack);
+
+        #[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+        {
+            x86_64::wmemchr(needle, &haystack);
+            x86_64::wmemrchr(needle, &haystack);
+            x86_64::wmemcount(needle, &haystack);
+        }
+
+        #[cfg(feature = "unstable")]
+        {
+            wmemchr::portable::wmemchr(needle, &haystack);
+            wmemchr::portable::wmemrchr(needle, &haystack);
+            wmemchr::portable::wmemcount(needle, &haystack);
+            wmemchr::portable::wmemchr_any(&[needle, 7], &haystack);
+        }
+    }
+}
+
+#[test]
+fn agreeing_string_searches() {
+    use wmemchr::wchar::{wchar_t, wcschr, wcslen, wcsstr};
+
+    let mut s: Vec<wchar_t> = (0..300).map(|i| (i % 50 + 1) as wchar_t).collect();
+    s.push(0);
+    let needle = [49, 50, 1, 0];
+
+    // Every start offset, so that the string is at every alignment.
+    for start in 0..s.len() {
+        unsafe {
+            let s = s.as_ptr().add(start);
+
+            wcslen(s);
+            wcschr(s, 25);
+            wcschr(s, 0);
+            wcsstr(s, needle.as_ptr());
+        }
+    }
+}
+
+#[test]
+#[should_panic(
+    expected = "wmemchr disagrees with naive on the custom backend: returned None, expected \
+                Some(120), needle 120, len 121"
+)]
+fn disagreeing_kernel() {
+    struct Broken;
+
+    impl Kernel<u8> for Broken {
+        fn wmemchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+            // Misses a match in the last element of longer haystacks.
+            let haystack = &haystack[..haystack.len().min(100)];
+            naive::wmemchr(needle, haystack)
+        }
+    }
+
+    let mut haystack = vec![b'a'; 121];
+    haystack[120] = b'x';
+
+    register_kernel::<u8, Broken>();
+    let result = std::panic::catch_unwind(|| wmemchr::wmemchr(b'x', &haystack));
+    unregister_kernel::<u8>();
+
+    std::panic::resume_unwind(result.unwrap_err());
+}
+
+#[test]
+#[should_panic(
+    expected = "wmemcount disagrees with naive on the custom backend: returned 1, expected 2"
+)]
+fn disagreeing_count_kernel() {
+    struct Broken;
+
+    impl Kernel<i16> for Broken {
+        fn wmemchr(needle: i16, haystack: &[i16]) -> Option<usize> {
+            naive::wmemchr(needle, haystack)
+        }
+
+        fn wmemcount(needle: i16, haystack: &[i16]) -> usize {
+            // Misses a match in the first element.
+            naive::wmemcount(needle, &haystack[1..])
+        }
+    }
+
+    register_kernel::<i16, Broken>();
+    let result = std::panic::catch_unwind(|| wmemchr::wmemcount(7_i16, &[7, 1, 7]));
+    unregister_kernel::<i16>();
+
+    std::panic::resume_unwind(result.unwrap_err());
+}
@@ -0,0 +1,133 @@
+macro_rules! tests {
+    ($($ty:ident),*) => {
+        $(
+            mod $ty {
+                use wmemchr::const_fn::$ty::{const_wmemchr, const_wmemmem, const_wmemrchr};
+
+                const A: $ty = b'a' as $ty;
+                const B: $ty = b'b' as $ty;
+                const C: $ty = b'c' as $ty;
+                const D: $ty = b'd' as $ty;
+                const Z: $ty = b'z' as $ty;
+
+                const HAYSTACK: &[$ty] = &[A, B, C, A, B, D];
+
+                const FIRST: Option<usize> = const_wmemchr(B, HAYSTACK);
+                const LAST: Option<usize> = const_wmemrchr(B, HAYSTACK);
+                const SUBSLICE: Option<usize> = const_wmemmem(&[A, B, D], HAYSTACK);
+
+                #[test]
+                fn const_eval() {
+                    assert_eq!(FIRST, Some(1));
+                    assert_eq!(LAST, Some(4));
+                    assert_eq!(SUBSLICE, Some(3));
+                }
+
+                #[test]
+                fn every_needle() {
+                    for &needle in &[A, B, C, D, Z] {
+                        assert_eq!(const_wmemchr(needle, HAYSTACK), HAYSTACK.iter().position(|&c| c == needle));
+                        assert_eq!(const_wmemrchr(needle, HAYSTACK), HAYSTACK.iter().rposition(|&c| c == needle));
+                    }
+                }
+
+                #[test]
+                fn every_subslice() {
+                    let haystack = [A, B, C, A, B, D, Z];
+
+                    for start in 0..haystack.len() {
+                        for end in start..=haystack.len() {
+                            let needle = &haystack[start..end];
+                            assert_eq!(
+                                const_wmemmem(needle, HAYSTACK),
+                                super::wmemmem(needle, HAYSTACK),
+                                "needle: {:?}",
+                                needle,
+                            );
+                        }
+                    }
+                }
+
+                #[cfg(not(miri))]
+                quickcheck::quickcheck! {
+                    fn wmemchr(needle: $ty, haystack: Vec<$ty>) -> bool {
+                        const_wmemchr(needle, &haystack) == haystack.iter().position(|&c| c == needle)
+                    }
+
+                    fn wmemrchr(needle: $ty, haystack: Vec<$ty>) -> bool {
+                        const_wmemrchr(needle, &haystack) == haystack.iter().rposition(|&c| c == needle)
+                    }
+
+                    fn wmemmem(needle: Vec<$ty>, haystack: Vec<$ty>) -> bool {
+                        const_wmemmem(&needle, &haystack) == super::wmemmem(&needle, &haystack)
+                    }
+                }
+            }
+        )*
+    };
+}
+tests! { u8, u16, u32, i8, i16, i32, char }
+
+macro_rules! bytes_tests {
+    ($($name:ident: $ty:ident),*) => {
+        $(
+            mod $name {
+                use wmemchr::const_fn::$ty::const_wmemchr_ne_bytes;
+
+                const SIZE: usize = core::mem::size_of::<$ty>();
+
+                const ONES: $ty = $ty::from_ne_bytes([1; SIZE]);
+                const FOUND: Option<usize> =
+                    const_wmemchr_ne_bytes(ONES, &[0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1]);
+
+                #[test]
+                fn const_eval() {
+                    assert_eq!(FOUND, Some(8 / SIZE));
+                }
+
+                #[test]
+                fn every_offset() {
+                    let units: Vec<$ty> = b"abcabd".iter().map(|&c| c as $ty).collect();
+
+                    for offset in 0..SIZE {
+                        let mut bytes = vec![0xff; offset];
+                        for unit in units.iter() {
+                            bytes.extend_from_slice(&unit.to_ne_bytes());
+                        }
+                        let haystack = &bytes[offset..];
+
+                        for needle in b"abcdz".iter().map(|&c| c as $ty) {
+                            assert_eq!(
+                                const_wmemchr_ne_bytes(needle, haystack),
+                                units.iter().position(|&c| c == needle),
+                                "offset: {}, needle: {}",
+                                offset,
+                                needle,
+                            );
+                        }
+                    }
+                }
+
+                #[cfg(not(miri))]
+                quickcheck::quickcheck! {
+                    fn wmemchr(needle: $ty, haystack: Vec<u8>, offset: usize) -> bool {
+                        let haystack = &haystack[offset % (haystack.len() + 1)..];
+                        let expected = haystack
+                            .chunks_exact(SIZE)
+                            .position(|unit| unit == &needle.to_ne_bytes()[..]);
+                        const_wmemchr_ne_bytes(needle, haystack) == expected
+                    }
+                }
+            }
+        )*
+    };
+}
+bytes_tests! { u16_bytes: u16, u32_bytes: u32, i16_bytes: i16, i32_bytes: i32 }
+
+/// Returns the index of the first occurrence of `needle` in `haystack`.
+fn wmemmem<T: PartialEq>(needle: &[T], haystack: &[T]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "std")]
+
+use std::env;
+
+use wmemchr::{backend, Backend};
+
+#[test]
+fn backend_from_env() {
+    let requested = env::var("WMEMCHR_BACKEND")
+        .ok()
+        .and_then(|name| Backend::ALL.iter().copied().find(|b| b.name() == name))
+        .filter(|b| b.is_supported());
+
+    if let Some(requested) = requested {
+        assert_eq!(backend::<u16>(), requested);
+    }
+}
+
+/// Tests that set the environment variable, by running the test binary again.
+///
+/// The backend is detected once per process, so the variable cannot be changed
+/// for a test running in this process.
+#[cfg(not(miri))]
+mod subprocess {
+    use std::env;
+    use std::process::Command;
+
+    use wmemchr::{backend, Backend};
+
+    /// Set in the environment of the test binary when it is run as a subprocess.
+    const CHILD: &str = "WMEMCHR_ENV_TEST_CHILD";
+
+    /// Returns the backend detected without the environment variable, which
+    /// is the most preferred supported backend.
+    fn detected() -> Backend {
+        Backend::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|b| b.is_supported())
+            .unwrap()
+    }
+
+    /// Runs the test `name` in a subprocess, with `WMEMCHR_BACKEND` set to
+    /// `value`.
+    fn run_with_backend(name: &str, value: &str) {
+        let status = Command::new(env::current_exe().unwrap())
+            .arg(name)
+            .arg("--exact")
+            .arg("--test-threads=1")
+            .env("WMEMCHR_BACKEND", value)
+            .env(CHILD, "1")
+            .status()
+            .unwrap();
+
+        assert!(status.success(), "WMEMCHR_BACKEND={}", value);
+    }
+
+    #[test]
+    fn unknown_backend_is_ignored() {
+        if env::var_os(CHILD).is_some() {
+            assert_eq!(backend::<u16>(), detected());
+            return;
+        }
+
+        run_with_backend("subprocess::unknown_backend_is_ignored", "not-a-backend");
+    }
+
+    #[test]
+    fn unsupported_backend_is_ignored() {
+        if env::var_os(CHILD).is_some() {
+            assert_eq!(backend::<u16>(), detected());
+            return;
+        }
+
+        // Every backend may be supported, in which case there is nothing to check.
+        if let Some(unsupported) = Backend::ALL.iter().find(|b| !b.is_supported()) {
+            run_with_backend(
+                "subprocess::unsupported_backend_is_ignored",
+                unsupported.name(),
+            );
+        }
+    }
+
+    #[test]
+    fn supported_backend_is_used() {
+        // The least preferred supported backend, which is only used if requested.
+        let requested = Backend::ALL
+            .iter()
+            .copied()
+            .find(|b| b.is_supported())
+            .unwrap();
+
+        if env::var_os(CHILD).is_some() {
+            assert_eq!(backend::<u16>(), requested);
+            return;
+        }
+
+        run_with_backend("subprocess::supported_backend_is_used", requested.name());
+    }
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "std")]
+
+use wmemchr::{IncrementalSearcher, WideRepr};
+
+/// Returns the offsets of every occurrence of a needle in a haystack.
+fn expected(needle: &[u16], haystack: &[u16]) -> Vec<u64> {
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, w)| *w == needle)
+        .map(|(i, _)| i as u64)
+        .collect()
+}
+
+fn units(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+/// Feeds a haystack in chunks of the given sizes, repeated as needed.
+fn feed(needle: &[u16], haystack: &[u16], sizes: &[usize]) -> Vec<u64> {
+    let mut searcher = IncrementalSearcher::new(needle);
+    let mut offsets = Vec::new();
+
+    let mut rest = haystack;
+    for &size in sizes.iter().cycle() {
+        let (chunk, tail) = rest.split_at(size.min(rest.len()));
+        searcher.feed(chunk, |offset| offsets.push(offset));
+        rest = tail;
+
+        if rest.is_empty() {
+            break;
+        }
+    }
+
+    assert_eq!(searcher.offset(), haystack.len() as u64);
+    offsets
+}
+
+#[test]
+fn every_chunk_size() {
+    let haystack = units("aabaaabaaaab abab ababab \r\n\r\r\n aaaaaaaa");
+    let needles = ["a", "aa", "aab", "abab", "\r\n", "aaaa", " a", "baaab", "x"];
+
+    for needle in needles.iter().map(|s| units(s)) {
+        let expected = expected(&needle, &haystack);
+
+        for size in 1..=haystack.len() {
+            assert_eq!(
+                feed(&needle, &haystack, &[size]),
+                expected,
+                "needle {:?}, chunk size {}",
+                String::from_utf16_lossy(&needle),
+                size,
+            );
+        }
+
+        // Chunks of uneven sizes, including empty chunks.
+        assert_eq!(feed(&needle, &haystack, &[3, 0, 1, 7, 2, 0, 5]), expected);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn long_haystack() {
+    let haystack: Vec<u16> = (0..10_000).map(|i| (i * 7 % 13) as u16).collect();
+    let needle = &haystack[500..509];
+
+    let expected = expected(needle, &haystack);
+    assert!(expected.len() > 1);
+
+    for &size in &[1, 5, 64, 1000, 4096] {
+        assert_eq!(feed(needle, &haystack, &[size]), expected);
+    }
+}
+
+#[test]
+fn reset() {
+    let needle = units("ab");
+    let mut searcher = IncrementalSearcher::new(&needle);
+    let mut offsets = Vec::new();
+
+    searcher.feed(&units("xxa"), |offset| offsets.push(offset));
+    searcher.reset();
+    searcher.feed(&units("bab"), |offset| offsets.push(offset));
+
+    assert_eq!(offsets, [1]);
+    assert_eq!(searcher.offset(), 3);
+}
+
+#[test]
+fn newtype() {
+    #[derive(Clone, Copy)]
+    #[repr(transparent)]
+    struct Utf16Unit(u16);
+
+    unsafe impl WideRepr for Utf16Unit {
+        type Repr = u16;
+    }
+
+    let wrap = |s: &str| -> Vec<Utf16Unit> { s.encode_utf16().map(Utf16Unit).collect() };
+
+    let mut searcher = IncrementalSearcher::new(&wrap("ab"));
+    let mut offsets = Vec::new();
+
+    searcher.feed(&wrap("xa"), |offset| offsets.push(offset));
+    searcher.feed(&wrap("bab"), |offset| offsets.push(offset));
+
+    assert_eq!(offsets, [1, 3]);
+}
+
+#[test]
+#[should_panic(expected = "the needle must not be empty")]
+fn empty_needle() {
+    IncrementalSearcher::<u16>::new(&[]);
+}
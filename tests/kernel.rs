@@ -0,0 +1,201 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use wmemchr::{check_kernel, naive, register_kernel, unregister_kernel, Kernel, Wide};
+
+struct Fallback;
+
+impl<T: Wide> Kernel<T> for Fallback {
+    fn wmemchr(needle: T, haystack: &[T]) -> Option<usize> {
+        wmemchr::fallback::wmemchr(needle, haystack)
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn check_fallback_kernel() {
+    check_kernel::<u8, Fallback>();
+    check_kernel::<u16, Fallback>();
+    check_kernel::<u32, Fallback>();
+    check_kernel::<i8, Fallback>();
+    check_kernel::<i16, Fallback>();
+    check_kernel::<i32, Fallback>();
+    check_kernel::<char, Fallback>();
+}
+
+#[cfg(all(not(miri), any(target_arch = "x86", target_arch = "x86_64")))]
+#[test]
+fn check_x86_64_kernel() {
+    #[cfg(target_arch = "x86")]
+    use wmemchr::x86 as x86_64;
+    #[cfg(target_arch = "x86_64")]
+    use wmemchr::x86_64;
+
+    struct X86_64;
+
+    impl<T: Wide> Kernel<T> for X86_64 {
+        fn wmemchr(needle: T, haystack: &[T]) -> Option<usize> {
+            x86_64::wmemchr(needle, haystack)
+        }
+
+        fn wmemrchr(needle: T, haystack: &[T]) -> Option<usize> {
+            x86_64::wmemrchr(needle, haystack)
+        }
+
+        fn wmemcount(needle: T, haystack: &[T]) -> usize {
+            x86_64::wmemcount(needle, haystack)
+        }
+    }
+
+    check_kernel::<u8, X86_64>();
+    check_kernel::<u16, X86_64>();
+    check_kernel::<u32, X86_64>();
+    check_kernel::<char, X86_64>();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[should_panic(expected = "wmemchr disagrees with naive")]
+fn check_broken_kernel() {
+    struct Broken;
+
+    impl Kernel<u16> for Broken {
+        fn wmemchr(needle: u16, haystack: &[u16]) -> Option<usize> {
+            // Misses a match in the last element of longer haystacks.
+            let haystack = &haystack[..haystack.len().min(100)];
+            naive::wmemchr(needle, haystack)
+        }
+    }
+
+    check_kernel::<u16, Broken>();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[should_panic(expected = "wmemchr disagrees with naive")]
+fn check_broken_huge_kernel() {
+    struct Broken;
+
+    impl Kernel<u32> for Broken {
+        fn wmemchr(needle: u32, haystack: &[u32]) -> Option<usize> {
+            // Misses matches past the first 768 bytes of huge haystacks.
+            let haystack = &haystack[..haystack.len().min(192)];
+            naive::wmemchr(needle, haystack)
+        }
+    }
+
+    check_kernel::<u32, Broken>();
+}
+
+#[test]
+fn register_custom_kernel() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counting;
+
+    impl Kernel<u32> for Counting {
+        fn wmemchr(needle: u32, haystack: &[u32]) -> Option<usize> {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            naive::wmemchr(needle, haystack)
+        }
+
+        fn wmemrchr(needle: u32, haystack: &[u32]) -> Option<usize> {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            naive::wmemrchr(needle, haystack)
+        }
+
+        fn wmemcount(needle: u32, haystack: &[u32]) -> usize {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            naive::wmemcount(needle, haystack)
+        }
+
+        fn wmemchr_any(needles: &[u32], haystack: &[u32]) -> Option<usize> {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            naive::wmemchr_any(needles, haystack)
+        }
+    }
+
+    let haystack: Vec<char> = "foo bar".chars().collect();
+
+    register_kernel::<u32, Counting>();
+
+    // The kernel is used for every type represented by `u32`.
+    assert_eq!(wmemchr::wmemchr(0x6f_u32, &[0x66, 0x6f, 0x6f]), Some(1));
+    assert_eq!(
+        wmemchr::wmemchr_swapped(0x6f00_0000_u32, &[0x66, 0x6f]),
+        Some(1)
+    );
+    assert_eq!(wmemchr::wmemrchr(0x6f_u32, &[0x66, 0x6f, 0x6f]), Some(2));
+    assert_eq!(
+        wmemchr::wmemrchr_swapped(0x6f00_0000_u32, &[0x6f, 0x66]),
+        Some(0)
+    );
+    assert_eq!(wmemchr::wmemcount(0x6f_u32, &[0x66, 0x6f, 0x6f]), 2);
+    assert_eq!(
+        wmemchr::wmemcount_swapped(0x6f00_0000_u32, &[0x66, 0x6f]),
+        1
+    );
+    assert_eq!(
+        wmemchr::wmemchr_any(&[0x61_u32, 0x62], &[0x66, 0x62]),
+        Some(1)
+    );
+    assert_eq!(CALLS.load(Ordering::Relaxed), 7);
+
+    // The default operations use the crate's kernels.
+    assert_eq!(
+        wmemchr::wmemchr_range(0x61_u32..=0x62, &[0x66, 0x62]),
+        Some(1)
+    );
+    assert_eq!(CALLS.load(Ordering::Relaxed), 7);
+
+    // Kernels are registered for each type separately.
+    assert_eq!(wmemchr::wmemchr('o', &haystack), Some(1));
+    assert_eq!(wmemchr::wmemcount('o', &haystack), 2);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 7);
+
+    unregister_kernel::<u32>();
+
+    assert_eq!(wmemchr::wmemchr(0x6f_u32, &[0x66, 0x6f, 0x6f]), Some(1));
+    assert_eq!(wmemchr::wmemcount(0x6f_u32, &[0x66, 0x6f, 0x6f]), 2);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 7);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[should_panic(expected = "wmemcount disagrees with naive")]
+fn check_broken_count_kernel() {
+    struct Broken;
+
+    impl Kernel<u32> for Broken {
+        fn wmemchr(needle: u32, haystack: &[u32]) -> Option<usize> {
+            naive::wmemchr(needle, haystack)
+        }
+
+        fn wmemcount(needle: u32, haystack: &[u32]) -> usize {
+            // Misses a match in the first element.
+            naive::wmemcount(needle, haystack.get(1..).unwrap_or_default())
+        }
+    }
+
+    check_kernel::<u32, Broken>();
+}
+
+#[test]
+#[should_panic(expected = "registered wmemchr kernel returned index 3 out of bounds")]
+fn out_of_bounds_kernel() {
+    struct OutOfBounds;
+
+    impl Kernel<i16> for OutOfBounds {
+        fn wmemchr(_: i16, haystack: &[i16]) -> Option<usize> {
+            Some(haystack.len())
+        }
+    }
+
+    let haystack = [0x66_i16, 0x6f, 0x6f];
+
+    // No other test searches `i16` with the registered kernel, so it is left
+    // registered when the search panics.
+    register_kernel::<i16, OutOfBounds>();
+
+    // The index is checked before a pointer to the element is made.
+    unsafe { wmemchr::wmemchr_nonnull(0x6f_i16, haystack.as_ptr(), haystack.len()) };
+}
@@ -0,0 +1,38 @@
+//! Differential tests against libc, whose `wchar_t` is 32-bit on Linux.
+
+#![cfg(all(target_os = "linux", not(miri)))]
+
+use quickcheck::quickcheck;
+
+/// Searches a haystack with libc `wmemchr`.
+fn libc_wmemchr(needle: i32, haystack: &[i32]) -> Option<usize> {
+    let ptr = haystack.as_ptr() as *const libc::wchar_t;
+
+    let found = unsafe { libc::wmemchr(ptr, needle as libc::wchar_t, haystack.len()) };
+    if found.is_null() {
+        None
+    } else {
+        Some(unsafe { found.offset_from(ptr) } as usize)
+    }
+}
+
+quickcheck! {
+    fn wmemchr(needle: i32, haystack: Vec<i32>) -> bool {
+        wmemchr::wmemchr(needle, &haystack) == libc_wmemchr(needle, &haystack)
+    }
+
+    // Draws from a few distinct characters, so that the needle is often found.
+    fn wmemchr_dense(needle: u8, haystack: Vec<u8>) -> bool {
+        let needle = i32::from(needle % 8) - 4;
+        let haystack: Vec<i32> = haystack.iter().map(|&c| i32::from(c % 8) - 4).collect();
+
+        wmemchr::wmemchr(needle, &haystack) == libc_wmemchr(needle, &haystack)
+    }
+
+    fn wcslen(s: Vec<i32>) -> bool {
+        let mut s: Vec<libc::wchar_t> = s.into_iter().map(|c| c as libc::wchar_t).collect();
+        s.push(0);
+
+        unsafe { wmemchr::wchar::wcslen(s.as_ptr()) == libc::wcslen(s.as_ptr()) }
+    }
+}
@@ -1,5 +1,14 @@
 #![cfg(not(miri))]
 
+/// Picks a needle from the haystack, so that matches are likely, unless the
+/// haystack is empty.
+fn pick<T: Copy>(needle: T, haystack: &[T], index: usize) -> T {
+    match haystack.len() {
+        0 => needle,
+        len => haystack[index % len],
+    }
+}
+
 macro_rules! tests {
     ($($ty:ident),*) => {
         $(
@@ -8,6 +17,10 @@ macro_rules! tests {
                 use wmemchr::fallback;
                 use wmemchr::naive;
 
+                use crate::pick;
+
+                #[cfg(target_arch = "x86")]
+                use wmemchr::x86 as x86_64;
                 #[cfg(target_arch = "x86_64")]
                 use wmemchr::x86_64;
 
@@ -15,16 +28,298 @@ macro_rules! tests {
                     fn fallback(needle: $ty, haystack: Vec<$ty>) -> bool {
                         fallback::wmemchr(needle, &haystack) == naive::wmemchr(needle, &haystack)
                     }
+
+                    fn fallback_reverse(needle: $ty, haystack: Vec<$ty>, index: usize) -> bool {
+                        let needle = pick(needle, &haystack, index);
+
+                        fallback::wmemrchr(needle, &haystack) == naive::wmemrchr(needle, &haystack)
+                    }
+
+                    fn fallback_count(needle: $ty, haystack: Vec<$ty>, index: usize) -> bool {
+                        let needle = pick(needle, &haystack, index);
+
+                        fallback::wmemcount(needle, &haystack) == naive::wmemcount(needle, &haystack)
+                    }
                 }
 
-                #[cfg(target_arch = "x86_64")]
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
                 quickcheck! {
                     fn x86_64(needle: $ty, haystack: Vec<$ty>) -> bool {
                         x86_64::wmemchr(needle, &haystack) == naive::wmemchr(needle, &haystack)
                     }
+
+                    fn x86_64_reverse(needle: $ty, haystack: Vec<$ty>, index: usize) -> bool {
+                        let needle = pick(needle, &haystack, index);
+
+                        x86_64::wmemrchr(needle, &haystack) == naive::wmemrchr(needle, &haystack)
+                    }
+
+                    fn x86_64_count(needle: $ty, haystack: Vec<$ty>, index: usize) -> bool {
+                        let needle = pick(needle, &haystack, index);
+
+                        x86_64::wmemcount(needle, &haystack) == naive::wmemcount(needle, &haystack)
+                    }
                 }
             }
         )*
     };
 }
-tests! { u16, u32, i16, i32 }
+tests! { u8, u16, u32, i8, i16, i32, char }
+
+mod repr {
+    use quickcheck::quickcheck;
+    use wmemchr::{naive, WideRepr};
+
+    #[derive(Clone, Copy)]
+    #[repr(transparent)]
+    struct Utf16Unit(u16);
+
+    unsafe impl WideRepr for Utf16Unit {
+        type Repr = u16;
+    }
+
+    quickcheck! {
+        fn newtype(needle: u16, haystack: Vec<u16>) -> bool {
+            let units: Vec<Utf16Unit> = haystack.iter().copied().map(Utf16Unit).collect();
+
+            wmemchr::wmemchr(Utf16Unit(needle), &units) == naive::wmemchr(needle, &haystack)
+        }
+
+        fn newtype_swapped(needle: u16, haystack: Vec<u16>) -> bool {
+            let units: Vec<Utf16Unit> = haystack.iter().copied().map(Utf16Unit).collect();
+            let swapped: Vec<u16> = haystack.iter().map(|c| c.swap_bytes()).collect();
+
+            wmemchr::wmemchr_swapped(Utf16Unit(needle), &units) == naive::wmemchr(needle, &swapped)
+        }
+
+        fn newtype_swap_endianness(data: Vec<u16>) -> bool {
+            let mut units: Vec<Utf16Unit> = data.iter().copied().map(Utf16Unit).collect();
+            wmemchr::swap_endianness(&mut units);
+
+            units.iter().zip(&data).all(|(unit, c)| unit.0 == c.swap_bytes())
+        }
+    }
+}
+
+mod bytes {
+    use quickcheck::quickcheck;
+    use wmemchr::naive;
+
+    use crate::pick;
+
+    quickcheck! {
+        fn bytes_le(needle: u16, haystack: Vec<u8>, offset: u8, index: usize) -> bool {
+            let haystack = &haystack[(offset as usize).min(haystack.len())..];
+            let units: Vec<u16> = haystack
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            let needle = pick(needle, &units, index);
+
+            wmemchr::wmemchr_bytes_le(needle, haystack) == naive::wmemchr(needle, &units)
+        }
+
+        fn bytes_be(needle: u16, haystack: Vec<u8>, offset: u8, index: usize) -> bool {
+            let haystack = &haystack[(offset as usize).min(haystack.len())..];
+            let units: Vec<u16> = haystack
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            let needle = pick(needle, &units, index);
+
+            wmemchr::wmemchr_bytes_be(needle, haystack) == naive::wmemchr(needle, &units)
+        }
+
+        fn bytes32_le(needle: u32, haystack: Vec<u8>, offset: u8, index: usize) -> bool {
+            let haystack = &haystack[(offset as usize).min(haystack.len())..];
+            let units: Vec<u32> = haystack
+                .chunks_exact(4)
+                .map(|unit| u32::from_le_bytes([unit[0], unit[1], unit[2], unit[3]]))
+                .collect();
+            let needle = pick(needle, &units, index);
+
+            wmemchr::wmemchr32_bytes_le(needle, haystack) == naive::wmemchr(needle, &units)
+        }
+
+        fn bytes32_be(needle: u32, haystack: Vec<u8>, offset: u8, index: usize) -> bool {
+            let haystack = &haystack[(offset as usize).min(haystack.len())..];
+            let units: Vec<u32> = haystack
+                .chunks_exact(4)
+                .map(|unit| u32::from_be_bytes([unit[0], unit[1], unit[2], unit[3]]))
+                .collect();
+            let needle = pick(needle, &units, index);
+
+            wmemchr::wmemchr32_bytes_be(needle, haystack) == naive::wmemchr(needle, &units)
+        }
+    }
+}
+
+macro_rules! swapped_tests {
+    ($($ty:ident),*) => {
+        mod swapped {
+            $(
+                mod $ty {
+                    use quickcheck::quickcheck;
+                    use wmemchr::fallback;
+                    use wmemchr::naive;
+
+                    #[cfg(target_arch = "x86")]
+                    use wmemchr::x86 as x86_64;
+                    #[cfg(target_arch = "x86_64")]
+                    use wmemchr::x86_64;
+
+                    fn swapped(haystack: &[$ty]) -> Vec<$ty> {
+                        haystack.iter().map(|c| c.swap_bytes()).collect()
+                    }
+
+                    quickcheck! {
+                        fn swap_endianness(data: Vec<$ty>) -> bool {
+                            let mut swapped_data = data.clone();
+                            wmemchr::swap_endianness(&mut swapped_data);
+                            swapped_data == swapped(&data)
+                        }
+
+                        fn fallback(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            fallback::wmemchr_swapped(needle, &haystack)
+                                == naive::wmemchr(needle, &swapped(&haystack))
+                        }
+
+                        fn fallback_reverse(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            fallback::wmemrchr_swapped(needle, &haystack)
+                                == naive::wmemrchr(needle, &swapped(&haystack))
+                        }
+
+                        fn fallback_count(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            fallback::wmemcount_swapped(needle, &haystack)
+                                == naive::wmemcount(needle, &swapped(&haystack))
+                        }
+
+                        fn naive_reverse(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            naive::wmemrchr_swapped(needle, &haystack)
+                                == naive::wmemrchr(needle, &swapped(&haystack))
+                        }
+
+                        fn naive_count(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            naive::wmemcount_swapped(needle, &haystack)
+                                == naive::wmemcount(needle, &swapped(&haystack))
+                        }
+
+                        fn crate_root(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            let expected = swapped(&haystack);
+
+                            wmemchr::wmemchr_swapped(needle, &haystack)
+                                == naive::wmemchr(needle, &expected)
+                                && wmemchr::wmemrchr_swapped(needle, &haystack)
+                                    == naive::wmemrchr(needle, &expected)
+                                && wmemchr::wmemcount_swapped(needle, &haystack)
+                                    == naive::wmemcount(needle, &expected)
+                        }
+                    }
+
+                    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                    quickcheck! {
+                        fn x86_64(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            x86_64::wmemchr_swapped(needle, &haystack)
+                                == naive::wmemchr(needle, &swapped(&haystack))
+                        }
+
+                        fn x86_64_reverse(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            x86_64::wmemrchr_swapped(needle, &haystack)
+                                == naive::wmemrchr(needle, &swapped(&haystack))
+                        }
+
+                        fn x86_64_count(needle: $ty, haystack: Vec<$ty>) -> bool {
+                            x86_64::wmemcount_swapped(needle, &haystack)
+                                == naive::wmemcount(needle, &swapped(&haystack))
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+swapped_tests! { u8, u16, u32, i8, i16, i32 }
+
+macro_rules! set_tests {
+    ($($ty:ident),*) => {
+        mod set {
+            $(
+                mod $ty {
+                    use quickcheck::quickcheck;
+                    use wmemchr::naive;
+
+                    // Fold values into a small range so that matches are likely.
+                    fn fold(data: &[$ty]) -> Vec<$ty> {
+                        data.iter().map(|c| c % 64).collect()
+                    }
+
+                    quickcheck! {
+                        fn any(needles: Vec<$ty>, haystack: Vec<$ty>) -> bool {
+                            let (needles, haystack) = (fold(&needles), fold(&haystack));
+
+                            wmemchr::wmemchr_any(&needles, &haystack)
+                                == naive::wmemchr_any(&needles, &haystack)
+                        }
+
+                        fn range(start: $ty, end: $ty, haystack: Vec<$ty>) -> bool {
+                            let (start, end, haystack) = (start % 64, end % 64, fold(&haystack));
+
+                            wmemchr::wmemchr_range(start..=end, &haystack)
+                                == naive::wmemchr_range(start..=end, &haystack)
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+set_tests! { u8, u16, u32, i16 }
+
+#[cfg(feature = "unstable")]
+macro_rules! portable_tests {
+    ($($ty:ident),*) => {
+        mod portable {
+            $(
+                mod $ty {
+                    use quickcheck::quickcheck;
+                    use wmemchr::naive;
+                    use wmemchr::portable;
+
+                    use crate::pick;
+
+                    quickcheck! {
+                        fn forward(needle: $ty, haystack: Vec<$ty>, index: usize) -> bool {
+                            let needle = pick(needle, &haystack, index);
+
+                            portable::wmemchr(needle, &haystack) == naive::wmemchr(needle, &haystack)
+                        }
+
+                        fn reverse(needle: $ty, haystack: Vec<$ty>, index: usize) -> bool {
+                            let needle = pick(needle, &haystack, index);
+
+                            portable::wmemrchr(needle, &haystack) == naive::wmemrchr(needle, &haystack)
+                        }
+
+                        fn count(needle: $ty, haystack: Vec<$ty>, index: usize) -> bool {
+                            let needle = pick(needle, &haystack, index);
+
+                            portable::wmemcount(needle, &haystack) == naive::wmemcount(needle, &haystack)
+                        }
+
+                        fn any(needle: $ty, haystack: Vec<$ty>, indices: Vec<usize>) -> bool {
+                            let needles: Vec<$ty> = indices
+                                .iter()
+                                .map(|&index| pick(needle, &haystack, index))
+                                .collect();
+
+                            portable::wmemchr_any(&needles, &haystack)
+                                == naive::wmemchr_any(&needles, &haystack)
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+#[cfg(feature = "unstable")]
+portable_tests! { u8, u16, u32, i8, i16, i32, char }
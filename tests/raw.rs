@@ -0,0 +1,100 @@
+use std::ptr;
+
+use wmemchr::{
+    naive, wmemchr_any_nonnull, wmemchr_any_raw, wmemchr_nonnull, wmemchr_range_nonnull,
+    wmemchr_range_raw, wmemchr_raw, wmemchr_swapped_nonnull, wmemchr_swapped_raw, wmemcount_raw,
+    wmemcount_swapped_raw, wmemrchr_nonnull, wmemrchr_raw, wmemrchr_swapped_nonnull,
+    wmemrchr_swapped_raw,
+};
+
+#[test]
+fn empty_null_haystack() {
+    unsafe {
+        assert_eq!(wmemchr_raw(0u16, ptr::null(), 0), ptr::null());
+        assert_eq!(wmemchr_nonnull(0u32, ptr::null(), 0), None);
+        assert_eq!(wmemchr_any_raw(&[0u16], ptr::null(), 0), ptr::null());
+        assert_eq!(wmemchr_any_nonnull(&[0u16], ptr::null(), 0), None);
+        assert_eq!(wmemchr_range_raw(0u16..=10, ptr::null(), 0), ptr::null());
+        assert_eq!(wmemchr_range_nonnull(0u16..=10, ptr::null(), 0), None);
+        assert_eq!(wmemchr_swapped_raw(0u16, ptr::null(), 0), ptr::null());
+        assert_eq!(wmemchr_swapped_nonnull(0u16, ptr::null(), 0), None);
+        assert_eq!(wmemrchr_raw(0u16, ptr::null(), 0), ptr::null());
+        assert_eq!(wmemrchr_nonnull(0u32, ptr::null(), 0), None);
+        assert_eq!(wmemrchr_swapped_raw(0u16, ptr::null(), 0), ptr::null());
+        assert_eq!(wmemrchr_swapped_nonnull(0u32, ptr::null(), 0), None);
+        assert_eq!(wmemcount_raw(0u16, ptr::null(), 0), 0);
+        assert_eq!(wmemcount_swapped_raw(0u16, ptr::null(), 0), 0);
+    }
+}
+
+#[test]
+fn agrees_with_slices() {
+    let haystack: Vec<u16> = (0..100).map(|i| (i % 61) as u16).collect();
+
+    for len in 0..haystack.len() {
+        let (ptr, haystack) = (haystack.as_ptr(), &haystack[..len]);
+
+        for needle in [0, 30, 60, 70].iter().copied() {
+            let expected = naive::wmemchr(needle, haystack);
+            let expected_ptr = expected.map_or(ptr::null(), |pos| unsafe { ptr.add(pos) });
+
+            unsafe {
+                assert_eq!(wmemchr_raw(needle, ptr, len), expected_ptr);
+                assert_eq!(wmemchr_any_raw(&[needle, 80], ptr, len), expected_ptr);
+                assert_eq!(wmemchr_range_raw(needle..=needle, ptr, len), expected_ptr);
+                assert_eq!(
+                    wmemchr_swapped_raw(needle.swap_bytes(), ptr, len),
+                    expected_ptr
+                );
+                assert_eq!(
+                    wmemchr_nonnull(needle, ptr, len).map(|p| p.as_ptr() as *const u16),
+                    expected.map(|pos| ptr.add(pos)),
+                );
+                assert_eq!(
+                    wmemchr_any_nonnull(&[needle, 80], ptr, len).map(|p| p.as_ptr() as *const u16),
+                    expected.map(|pos| ptr.add(pos)),
+                );
+                assert_eq!(
+                    wmemchr_range_nonnull(needle..=needle, ptr, len)
+                        .map(|p| p.as_ptr() as *const u16),
+                    expected.map(|pos| ptr.add(pos)),
+                );
+                assert_eq!(
+                    wmemchr_swapped_nonnull(needle.swap_bytes(), ptr, len)
+                        .map(|p| p.as_ptr() as *const u16),
+                    expected.map(|pos| ptr.add(pos)),
+                );
+            }
+
+            let expected = naive::wmemrchr(needle, haystack);
+            let expected_ptr = expected.map_or(ptr::null(), |pos| unsafe { ptr.add(pos) });
+
+            unsafe {
+                assert_eq!(wmemrchr_raw(needle, ptr, len), expected_ptr);
+                assert_eq!(
+                    wmemrchr_swapped_raw(needle.swap_bytes(), ptr, len),
+                    expected_ptr
+                );
+                assert_eq!(
+                    wmemrchr_nonnull(needle, ptr, len).map(|p| p.as_ptr() as *const u16),
+                    expected.map(|pos| ptr.add(pos)),
+                );
+                assert_eq!(
+                    wmemrchr_swapped_nonnull(needle.swap_bytes(), ptr, len)
+                        .map(|p| p.as_ptr() as *const u16),
+                    expected.map(|pos| ptr.add(pos)),
+                );
+            }
+
+            let expected = naive::wmemcount(needle, haystack);
+
+            unsafe {
+                assert_eq!(wmemcount_raw(needle, ptr, len), expected);
+                assert_eq!(
+                    wmemcount_swapped_raw(needle.swap_bytes(), ptr, len),
+                    expected
+                );
+            }
+        }
+    }
+}
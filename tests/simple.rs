@@ -86,3 +86,151 @@ macro_rules! tests {
     };
 }
 tests! { u16, u32, i16, i32 }
+
+mod unrolled {
+    use wmemchr::wmemchr;
+
+    /// Searches haystacks long enough for the unrolled loops, with the needle
+    /// in each vector of the loop.
+    #[test]
+    fn i32() {
+        for len in 0..200 {
+            let mut haystack: Vec<i32> = vec![0; len];
+            assert_eq!(wmemchr(1, &haystack), None, "len: {}", len);
+
+            for pos in 0..len {
+                haystack[pos] = 1;
+                assert_eq!(wmemchr(1, &haystack), Some(pos), "len: {}", len);
+                haystack[pos] = 0;
+            }
+        }
+    }
+}
+
+mod char {
+    use wmemchr::wmemchr;
+
+    #[test]
+    fn ascii() {
+        let haystack: Vec<char> = "abcda".chars().collect();
+
+        assert_eq!(wmemchr('a', &haystack), Some(0));
+        assert_eq!(wmemchr('c', &haystack), Some(2));
+        assert_eq!(wmemchr('z', &haystack), None);
+    }
+
+    #[test]
+    fn complex() {
+        let haystack: Vec<char> = "Löwe 老虎 Léopard Gepardi".chars().collect();
+
+        assert_eq!(wmemchr('ö', &haystack), Some(1));
+        assert_eq!(wmemchr('虎', &haystack), Some(6));
+        assert_eq!(wmemchr('é', &haystack), Some(9));
+        assert_eq!(wmemchr('o', &haystack), Some(10));
+        assert_eq!(wmemchr('京', &haystack), None);
+    }
+
+    #[test]
+    fn emoji() {
+        let haystack: Vec<char> = "🦀💖🧡💚💙".chars().collect();
+
+        assert_eq!(wmemchr('🦀', &haystack), Some(0));
+        assert_eq!(wmemchr('💖', &haystack), Some(1));
+        assert_eq!(wmemchr('💚', &haystack), Some(3));
+        assert_eq!(wmemchr('💜', &haystack), None);
+        assert_eq!(wmemchr('💝', &haystack), None);
+    }
+}
+
+macro_rules! size_tests {
+    ($($ty:ident),*) => {
+        mod sizes {
+            use wmemchr::{wmemchr, wmemcount, wmemrchr};
+
+            $(
+                /// Searches haystacks of each size class, with the needle at
+                /// every position near the start and end of the haystack.
+                #[test]
+                fn $ty() {
+                    let lens = (0..100).chain([2047, 2048, 4095, 4096, 8191, 8192, 20000].iter().copied());
+
+                    for len in lens {
+                        let mut haystack: Vec<$ty> = vec![0; len];
+                        assert_eq!(wmemchr(1, &haystack), None, "len: {}", len);
+                        assert_eq!(wmemrchr(1, &haystack), None, "len: {}", len);
+                        assert_eq!(wmemcount(0, &haystack), len, "len: {}", len);
+
+                        let positions = (0..len.min(300)).chain(len.saturating_sub(300)..len);
+                        for pos in positions {
+                            haystack[pos] = 1;
+                            assert_eq!(wmemchr(1, &haystack), Some(pos), "len: {}", len);
+                            assert_eq!(wmemrchr(1, &haystack), Some(pos), "len: {}", len);
+                            assert_eq!(wmemcount(1, &haystack), 1, "len: {}", len);
+                            haystack[pos] = 0;
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+size_tests! { u8, u16, u32 }
+
+macro_rules! bytes_tests {
+    ($($name:ident: $ty:ident, $search:ident, $to_bytes:ident;)*) => {
+        mod bytes {
+            $(
+                /// Searches byte slices starting at every offset from an
+                /// aligned buffer, with the needle at every position, so that
+                /// matches are found at odd byte offsets when the haystack is
+                /// unaligned.
+                #[test]
+                fn $name() {
+                    const SIZE: usize = core::mem::size_of::<$ty>();
+
+                    let needle: $ty = 0x0102;
+                    let filler: $ty = 0x0201;
+
+                    // Back the bytes with `u64`s, so that the offsets are
+                    // relative to an aligned address.
+                    let mut buf = [0u64; 8];
+
+                    for offset in 0..2 * SIZE {
+                        for len in 0..(buf.len() * 8 - offset) / SIZE {
+                            for pos in (0..len).map(Some).chain(Some(None)) {
+                                // SAFETY: Any bit pattern is a valid byte.
+                                let bytes = unsafe {
+                                    core::slice::from_raw_parts_mut(
+                                        buf.as_mut_ptr() as *mut u8,
+                                        buf.len() * 8,
+                                    )
+                                };
+                                let haystack = &mut bytes[offset..offset + len * SIZE + 1];
+
+                                for i in 0..len {
+                                    let unit = if Some(i) == pos { needle } else { filler };
+                                    haystack[i * SIZE..(i + 1) * SIZE]
+                                        .copy_from_slice(&unit.$to_bytes());
+                                }
+
+                                assert_eq!(
+                                    wmemchr::$search(needle, haystack),
+                                    pos,
+                                    "len: {}, offset: {}",
+                                    len,
+                                    offset,
+                                );
+                            }
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+bytes_tests! {
+    bytes_le: u16, wmemchr_bytes_le, to_le_bytes;
+    bytes_be: u16, wmemchr_bytes_be, to_be_bytes;
+    bytes32_le: u32, wmemchr32_bytes_le, to_le_bytes;
+    bytes32_be: u32, wmemchr32_bytes_be, to_be_bytes;
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "std")]
+
+use std::io::{self, Read};
+
+use wmemchr::{Endianness, StreamSearcher, SwapBytes, WideRepr};
+
+/// A reader that returns a few bytes at a time, interrupted and failing
+/// occasionally.
+struct Trickle<'a> {
+    data: &'a [u8],
+    reads: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reads += 1;
+        match self.reads % 11 {
+            3 => return Err(io::ErrorKind::Interrupted.into()),
+            7 => return Err(io::ErrorKind::TimedOut.into()),
+            _ => {}
+        }
+
+        let n = (1 + self.reads % 7).min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+/// Collects the offsets yielded by a searcher, skipping errors.
+fn offsets<R: Read, T: WideRepr>(searcher: StreamSearcher<R, T>) -> Vec<u64>
+where
+    T::Repr: SwapBytes,
+{
+    searcher.filter_map(Result::ok).collect()
+}
+
+/// Returns the offsets of every occurrence of a needle in a haystack.
+fn expected<T: PartialEq>(needle: &[T], haystack: &[T]) -> Vec<u64> {
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, w)| *w == needle)
+        .map(|(i, _)| i as u64)
+        .collect()
+}
+
+macro_rules! tests {
+    ($($ty:ident),*) => {
+        $(
+            mod $ty {
+                use super::*;
+
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                fn units(len: usize) -> Vec<$ty> {
+                    (0..len).map(|i| (i * 7 % 13) as $ty).collect()
+                }
+
+                fn encode(units: &[$ty], endianness: Endianness) -> Vec<u8> {
+                    units
+                        .iter()
+                        .flat_map(|&c| match endianness {
+                            Endianness::Little => c.to_le_bytes().to_vec(),
+                            Endianness::Big => c.to_be_bytes().to_vec(),
+                        })
+                        .collect()
+                }
+
+                #[test]
+                fn trickle() {
+                    let units = units(200);
+
+                    for &endianness in &[Endianness::Little, Endianness::Big] {
+                        let mut bytes = encode(&units, endianness);
+                        // A trailing partial code unit is ignored.
+                        bytes.extend_from_slice(&[0; SIZE - 1]);
+
+                        for needle in [&[0][..], &[7], &[12], &[13], &[7, 1, 8], &[0, 7, 1, 8, 2]].iter() {
+                            let reader = Trickle { data: &bytes, reads: 0 };
+                            let searcher = StreamSearcher::substring(reader, needle, endianness);
+
+                            assert_eq!(
+                                offsets(searcher),
+                                expected(needle, &units),
+                                "needle {:?}, {:?} endian",
+                                needle,
+                                endianness,
+                            );
+                        }
+                    }
+                }
+
+                #[test]
+                #[cfg_attr(miri, ignore)]
+                fn chunk_boundaries() {
+                    // Several chunks, with needles spanning their boundaries.
+                    let units = units(3 * 256 * 1024 / SIZE + 5);
+
+                    for &endianness in &[Endianness::Little, Endianness::Big] {
+                        let bytes = encode(&units, endianness);
+
+                        for needle in [&[7][..], &[5, 12, 6, 0, 7, 1]].iter() {
+                            let searcher = StreamSearcher::substring(&bytes[..], needle, endianness);
+                            assert_eq!(offsets(searcher), expected(needle, &units));
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+tests! { u16, u32 }
+
+#[test]
+fn errors_are_yielded() {
+    let bytes = [0u8; 64];
+    let reader = Trickle {
+        data: &bytes,
+        reads: 0,
+    };
+
+    let results: Vec<io::Result<u64>> =
+        StreamSearcher::new(reader, 0u16, Endianness::Little).collect();
+
+    assert!(results.iter().any(|r| r.is_err()));
+    let found: Vec<u64> = results.into_iter().filter_map(Result::ok).collect();
+    assert_eq!(found, (0..32).collect::<Vec<u64>>());
+}
+
+#[test]
+fn newtype() {
+    #[derive(Clone, Copy)]
+    #[repr(transparent)]
+    struct Utf16Unit(u16);
+
+    unsafe impl WideRepr for Utf16Unit {
+        type Repr = u16;
+    }
+
+    let utf16be = b"\0f\0o\0o\0 \0f\0o\0o";
+    let foo = [Utf16Unit(0x66), Utf16Unit(0x6f), Utf16Unit(0x6f)];
+
+    let searcher = StreamSearcher::substring(&utf16be[..], &foo, Endianness::Big);
+    assert_eq!(offsets(searcher), [0, 4]);
+}
+
+#[test]
+#[should_panic(expected = "the needle must not be empty")]
+fn empty_needle() {
+    StreamSearcher::<_, u16>::substring(&b""[..], &[], Endianness::Little);
+}
@@ -0,0 +1,61 @@
+#![cfg(all(feature = "testing", not(miri)))]
+
+use wmemchr::testing::{check_bytes, check_search, check_wmemchr, check_wmemcount, check_wmemrchr};
+use wmemchr::{force_backend, naive, Backend};
+
+macro_rules! check_kernels {
+    ($ty:ident, $($kernels:ident)::+) => {
+        check_wmemchr(|needle: $ty, haystack: &[$ty]| $($kernels::)+wmemchr(needle, haystack));
+        check_wmemrchr(|needle: $ty, haystack: &[$ty]| $($kernels::)+wmemrchr(needle, haystack));
+        check_wmemcount(|needle: $ty, haystack: &[$ty]| $($kernels::)+wmemcount(needle, haystack));
+    };
+}
+
+macro_rules! check_all {
+    ($($ty:ident),*) => {
+        $(
+            check_kernels!($ty, wmemchr);
+            check_kernels!($ty, wmemchr::fallback);
+            #[cfg(target_arch = "x86_64")]
+            check_kernels!($ty, wmemchr::x86_64);
+            #[cfg(target_arch = "x86")]
+            check_kernels!($ty, wmemchr::x86);
+            check_search(
+                |needle: $ty, haystack: &[$ty]| wmemchr::wmemchr_any(&[needle], haystack),
+                naive::wmemchr,
+            );
+            check_search(
+                |needle: $ty, haystack: &[$ty]| wmemchr::wmemchr_range(needle..=needle, haystack),
+                naive::wmemchr,
+            );
+        )*
+    };
+}
+
+#[test]
+fn every_backend() {
+    for &b in Backend::ALL.iter() {
+        if force_backend(b).is_ok() {
+            check_all!(u8, u16, u32, i8, i16, i32, char);
+
+            check_bytes(|needle: u16, haystack: &[u8]| {
+                wmemchr::wmemchr_bytes_le(u16::from_le(needle), haystack)
+            });
+            check_bytes(|needle: u16, haystack: &[u8]| {
+                wmemchr::wmemchr_bytes_be(u16::from_be(needle), haystack)
+            });
+            check_bytes(|needle: u32, haystack: &[u8]| {
+                wmemchr::wmemchr32_bytes_le(u32::from_le(needle), haystack)
+            });
+            check_bytes(|needle: u32, haystack: &[u8]| {
+                wmemchr::wmemchr32_bytes_be(u32::from_be(needle), haystack)
+            });
+        }
+    }
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn portable() {
+    check_kernels!(u16, wmemchr::portable);
+}
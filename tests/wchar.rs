@@ -0,0 +1,132 @@
+use std::ptr;
+
+use wmemchr::wchar::{wchar_t, wcschr, wcslen, wcsstr, wmemchr};
+
+/// Returns a null terminated wide string.
+fn wcs(s: &str) -> Vec<wchar_t> {
+    s.chars().map(|c| c as wchar_t).chain(Some(0)).collect()
+}
+
+#[test]
+fn wchar_t_matches_platform() {
+    use std::mem;
+
+    if cfg!(windows) {
+        assert_eq!(mem::size_of::<wchar_t>(), 2);
+    } else {
+        assert_eq!(mem::size_of::<wchar_t>(), 4);
+    }
+}
+
+#[test]
+fn wmemchr_semantics() {
+    let s = wcs("foo bar");
+    let p = s.as_ptr();
+
+    unsafe {
+        assert_eq!(wmemchr(p, 'o' as wchar_t, 7), p.add(1) as *mut _);
+        assert_eq!(wmemchr(p, 'r' as wchar_t, 6), ptr::null_mut());
+        assert_eq!(wmemchr(p, 0, 8), p.add(7) as *mut _);
+        assert_eq!(wmemchr(ptr::null(), 0, 0), ptr::null_mut());
+    }
+}
+
+#[test]
+fn wcslen_semantics() {
+    unsafe {
+        assert_eq!(wcslen(wcs("").as_ptr()), 0);
+        assert_eq!(wcslen(wcs("foo bar").as_ptr()), 7);
+        assert_eq!(wcslen(wcs("Löwe 老虎").as_ptr()), 7);
+    }
+}
+
+#[test]
+fn wcschr_semantics() {
+    let s = wcs("foo bar");
+    let p = s.as_ptr();
+
+    unsafe {
+        assert_eq!(wcschr(p, 'o' as wchar_t), p.add(1) as *mut _);
+        assert_eq!(wcschr(p, 'r' as wchar_t), p.add(6) as *mut _);
+        assert_eq!(wcschr(p, 'z' as wchar_t), ptr::null_mut());
+        // The terminator is part of the string.
+        assert_eq!(wcschr(p, 0), p.add(7) as *mut _);
+    }
+}
+
+#[test]
+fn wcsstr_semantics() {
+    let haystack = wcs("abcabdabd");
+    let p = haystack.as_ptr();
+
+    let find = |needle: &str| unsafe { wcsstr(p, wcs(needle).as_ptr()) };
+
+    unsafe {
+        assert_eq!(find(""), p as *mut _);
+        assert_eq!(find("a"), p as *mut _);
+        assert_eq!(find("abd"), p.add(3) as *mut _);
+        assert_eq!(find("bda"), p.add(4) as *mut _);
+        assert_eq!(find("dabd"), p.add(5) as *mut _);
+        assert_eq!(find("abcabdabd"), p as *mut _);
+        assert_eq!(find("abcabdabdx"), ptr::null_mut());
+        assert_eq!(find("abe"), ptr::null_mut());
+        assert_eq!(find("d"), p.add(5) as *mut _);
+    }
+
+    let empty = wcs("");
+    unsafe {
+        assert_eq!(
+            wcsstr(empty.as_ptr(), empty.as_ptr()),
+            empty.as_ptr() as *mut _
+        );
+        assert_eq!(wcsstr(empty.as_ptr(), wcs("a").as_ptr()), ptr::null_mut());
+    }
+}
+
+#[test]
+fn wcsstr_every_substring() {
+    let haystack = wcs("abracadabra");
+    let len = haystack.len() - 1;
+
+    for start in 0..len {
+        for end in start + 1..=len {
+            let mut needle = haystack[start..end].to_vec();
+            needle.push(0);
+
+            let expected = haystack[..len]
+                .windows(end - start)
+                .position(|w| w == &needle[..end - start])
+                .unwrap();
+
+            unsafe {
+                assert_eq!(
+                    wcsstr(haystack.as_ptr(), needle.as_ptr()),
+                    haystack.as_ptr().add(expected) as *mut _,
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn wcslen_wcschr_every_offset() {
+    // Long enough to need several vectors, with every start offset within them.
+    let s: Vec<wchar_t> = (0..300).map(|i| (i % 50 + 1) as wchar_t).chain(Some(0)).collect();
+
+    for start in 0..s.len() {
+        let p = s[start..].as_ptr();
+
+        unsafe {
+            assert_eq!(wcslen(p), s.len() - 1 - start);
+
+            for &c in &[1, 25, 50, 51, 0] {
+                let expected = s[start..]
+                    .iter()
+                    .position(|&x| x == c)
+                    .map_or(ptr::null_mut(), |pos| p.add(pos) as *mut _);
+
+                assert_eq!(wcschr(p, c), expected, "start {}, c {}", start, c);
+            }
+        }
+    }
+}